@@ -1,34 +1,102 @@
 mod astar;
+mod beam;
+mod checkpoint;
 mod closed_set;
+mod constructive;
 mod greedy;
 mod heuristic;
+mod hierarchical;
+mod ida_star;
 mod open_set;
+mod optimizer;
 mod output;
+mod rbfs;
+mod sma_star;
+mod solutions;
+mod transposition;
 mod uniform_cost;
 
+pub use beam::DEFAULT_BEAM_WIDTH;
+pub use checkpoint::DEFAULT_CHECKPOINT_INTERVAL;
 pub use heuristic::Heuristic;
-pub use output::Output;
+pub use hierarchical::DEFAULT_CORE_SIZE;
+pub use ida_star::MAX_THREADS;
+pub use open_set::{OpenList, OpenOptions, TieBreak};
+pub use optimizer::{optimize_path, Optimization, DEFAULT_WINDOW, MAX_WINDOW};
+pub use output::{Output, Phase, SearchStats};
+pub use sma_star::DEFAULT_NODE_BUDGET;
+pub use solutions::Solutions;
+pub use transposition::{Replacement, TableStats};
 
 use astar::astar;
+use checkpoint::{Checkpointer, Header, Session};
 use closed_set::ClosedSet;
+use ida_star::IdaOptions;
 use open_set::{OpenSet, OpenSetNode};
 
-use super::Puzzle;
-use anyhow::Result;
+use super::{Goal, Puzzle, Topology};
+use anyhow::{anyhow, Result};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Algorithm {
     AStar,
     UniformCost,
     Greedy,
+    /// Places the tiles one by one with fixed maneuvers, for boards too large to search
+    Constructive,
+    /// Iterative deepening A*, searching with memory linear in the solution length
+    IdaStar,
+    /// Constructive outer rings, then an IDA* search of the inner block
+    Hierarchical,
+    /// Recursive best-first search, with memory linear in the solution length
+    Rbfs,
+    /// Simplified memory-bounded A*, holding at most a budget of nodes
+    SmaStar,
+    /// Keeps the states of lowest heuristic at every depth, fast but incomplete
+    Beam,
 }
 
 impl Algorithm {
     pub fn is_heuristic(&self) -> bool {
-        if let Algorithm::UniformCost = self {
-            return false;
-        }
-        true
+        !matches!(self, Algorithm::UniformCost | Algorithm::Constructive)
+    }
+
+    /// Whether the algorithm finds the shortest solutions, given an admissible heuristic
+    pub fn is_optimal(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::AStar | Algorithm::UniformCost | Algorithm::IdaStar | Algorithm::Rbfs
+        )
+    }
+}
+
+/// Error of a search stopped by its timeout, told apart from the other errors with `Error::is`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timeout;
+
+impl std::fmt::Display for Timeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Timeout")
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Algorithm::AStar => "astar",
+            Algorithm::UniformCost => "uniform_cost",
+            Algorithm::Greedy => "greedy",
+            Algorithm::Constructive => "constructive",
+            Algorithm::IdaStar => "ida_star",
+            Algorithm::Hierarchical => "hierarchical",
+            Algorithm::Rbfs => "rbfs",
+            Algorithm::SmaStar => "sma_star",
+            Algorithm::Beam => "beam",
+        };
+        write!(f, "{}", name)
     }
 }
 
@@ -36,8 +104,18 @@ pub struct Solver {
     algorithm: Algorithm,
     heuristic: Heuristic,
     start_state: Puzzle,
+    goal: Goal,
     timeout: Option<u64>,
     verbose: bool,
+    core_size: usize,
+    ida_options: IdaOptions,
+    node_budget: usize,
+    beam_width: usize,
+    open_options: OpenOptions,
+    /// Checkpoint file and seconds between two checkpoints
+    checkpoint: Option<(String, u64)>,
+    /// Checkpoint the search resumes from
+    resume_path: Option<String>,
 }
 
 impl Solver {
@@ -48,24 +126,236 @@ impl Solver {
         timeout: Option<u64>,
         verbose: bool,
     ) -> Self {
+        let goal = Goal::snail(start_state.get_size());
         Self {
             algorithm,
             heuristic,
             start_state,
+            goal,
             timeout,
             verbose,
+            core_size: DEFAULT_CORE_SIZE,
+            ida_options: IdaOptions::default(),
+            node_budget: DEFAULT_NODE_BUDGET,
+            beam_width: DEFAULT_BEAM_WIDTH,
+            open_options: OpenOptions::default(),
+            checkpoint: None,
+            resume_path: None,
         }
     }
 
+    /// Solver resuming the search saved in the checkpoint at `path`, with its puzzle, goal,
+    /// algorithm, heuristic and open set options. The search keeps saving its state to the same file.
+    pub fn from_checkpoint(path: &str, timeout: Option<u64>, verbose: bool) -> Result<Self> {
+        let header = checkpoint::read_header(path)?;
+        let mut solver = Self::new(
+            header.algorithm,
+            header.heuristic,
+            header.start,
+            timeout,
+            verbose,
+        );
+        solver.goal = header.goal;
+        solver.open_options = header.open_options;
+        solver.checkpoint = Some((path.to_string(), DEFAULT_CHECKPOINT_INTERVAL));
+        solver.resume_path = Some(path.to_string());
+        Ok(solver)
+    }
+
+    /// Search towards the given goal instead of the snail goal
+    pub fn set_goal(&mut self, goal: Goal) {
+        self.goal = goal;
+    }
+
+    /// Search towards an arbitrary board of the same size
+    pub fn set_target(&mut self, target: Puzzle) {
+        self.goal = Goal::from_puzzle(target);
+    }
+
+    /// Let the blank wrap at the edges of the board, or stop there
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.start_state.set_topology(topology);
+    }
+
+    /// Side of the inner block searched by the hierarchical algorithm
+    pub fn set_core_size(&mut self, core_size: usize) {
+        self.core_size = core_size;
+    }
+
+    /// Number of worker threads of the ida_star and hierarchical searches
+    pub fn set_threads(&mut self, threads: usize) {
+        self.ida_options.threads = threads;
+    }
+
+    /// Keep the lower bounds proven by the ida_star and hierarchical searches
+    /// in a table of `capacity` entries
+    pub fn set_transposition_table(&mut self, capacity: usize, policy: Replacement) {
+        self.ida_options.table = Some((capacity, policy));
+    }
+
+    /// Number of nodes the sma_star search may hold at once
+    pub fn set_node_budget(&mut self, node_budget: usize) {
+        self.node_budget = node_budget;
+    }
+
+    /// Number of states the beam search keeps at every depth
+    pub fn set_beam_width(&mut self, beam_width: usize) {
+        self.beam_width = beam_width;
+    }
+
+    /// Order of the nodes of equal total cost in the astar, uniform_cost and greedy searches
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.open_options.tie_break = Some(tie_break);
+    }
+
+    /// How the astar, uniform_cost and greedy searches keep their open set
+    pub fn set_open_list(&mut self, list: OpenList) {
+        self.open_options.list = list;
+    }
+
+    pub fn set_open_options(&mut self, options: OpenOptions) {
+        self.open_options = options;
+    }
+
+    /// Save the state of the astar, uniform_cost, greedy or ida_star search to `path`
+    /// every `interval` seconds
+    pub fn set_checkpoint(&mut self, path: &str, interval: u64) {
+        self.checkpoint = Some((path.to_string(), interval));
+    }
+
+    /// `goal` is the goal of the search, prepared for its heuristic
+    fn session(&self, goal: &Goal) -> Result<Session> {
+        if self.checkpoint.is_none() && self.resume_path.is_none() {
+            return Ok(Session::default());
+        }
+        if !matches!(
+            self.algorithm,
+            Algorithm::AStar | Algorithm::UniformCost | Algorithm::Greedy | Algorithm::IdaStar
+        ) {
+            return Err(anyhow!(
+                "Checkpoints can only be used with the astar, uniform_cost, greedy or ida_star algorithm."
+            ));
+        }
+        let checkpointer = self.checkpoint.as_ref().map(|(path, interval)| {
+            let header = Header {
+                algorithm: self.algorithm,
+                heuristic: self.heuristic,
+                start: self.start_state.clone(),
+                goal: self.goal.clone(),
+                open_options: self.open_options,
+            };
+            Checkpointer::new(path, Duration::from_secs(*interval), header)
+        });
+        let resume = self
+            .resume_path
+            .as_deref()
+            .map(|path| checkpoint::read_resume(path, goal))
+            .transpose()?;
+        Ok(Session {
+            checkpointer,
+            resume,
+        })
+    }
+
     pub fn solve(&self) -> Result<Output> {
+        let topology = self.start_state.get_topology();
+        if topology == Topology::Torus
+            && matches!(
+                self.algorithm,
+                Algorithm::Constructive | Algorithm::Hierarchical
+            )
+        {
+            return Err(anyhow!(
+                "The {} algorithm cannot be used on a torus board.",
+                self.algorithm
+            ));
+        }
+        if !self.start_state.is_solvable_to(&self.goal)? {
+            return Err(anyhow!(
+                "No solution: the goal cannot be reached from this puzzle."
+            ));
+        }
+        // The hierarchical algorithm only searches the inner block, with a goal of its own
+        let goal = if self.algorithm.is_heuristic() && self.algorithm != Algorithm::Hierarchical {
+            self.heuristic.check_topology(topology)?;
+            self.heuristic.prepare_goal(&self.goal)?
+        } else {
+            self.goal.clone()
+        };
+        let (puzzle, goal) = (&self.start_state, &goal);
+        let session = self.session(goal)?;
         let output = match self.algorithm {
-            Algorithm::AStar => astar::solve(&self.start_state, self.heuristic, self.timeout)?,
-            Algorithm::UniformCost => uniform_cost::solve(&self.start_state, self.timeout)?,
-            Algorithm::Greedy => greedy::solve(&self.start_state, self.heuristic, self.timeout)?,
+            Algorithm::AStar => astar::solve(
+                puzzle,
+                goal,
+                self.heuristic,
+                self.timeout,
+                self.open_options,
+                session,
+            )?,
+            Algorithm::UniformCost => {
+                uniform_cost::solve(puzzle, goal, self.timeout, self.open_options, session)?
+            }
+            Algorithm::Greedy => greedy::solve(
+                puzzle,
+                goal,
+                self.heuristic,
+                self.timeout,
+                self.open_options,
+                session,
+            )?,
+            Algorithm::Constructive => constructive::solve(puzzle, goal, self.timeout)?,
+            Algorithm::IdaStar => ida_star::solve_with(
+                puzzle,
+                goal,
+                self.heuristic,
+                self.timeout,
+                self.ida_options,
+                session,
+            )?,
+            Algorithm::Hierarchical => hierarchical::solve(
+                puzzle,
+                goal,
+                self.heuristic,
+                self.core_size,
+                self.timeout,
+                self.ida_options,
+            )?,
+            Algorithm::Rbfs => rbfs::solve(puzzle, goal, self.heuristic, self.timeout)?,
+            Algorithm::SmaStar => {
+                sma_star::solve(puzzle, goal, self.heuristic, self.node_budget, self.timeout)?
+            }
+            Algorithm::Beam => {
+                beam::solve(puzzle, goal, self.heuristic, self.beam_width, self.timeout)?
+            }
         };
         Ok(output)
     }
 
+    /// Count the optimal solutions, given the length of a known solution, and list up to `cap` of them
+    pub fn optimal_solutions(&self, bound: usize, cap: Option<usize>) -> Result<Solutions> {
+        self.goal.check_size(&self.start_state)?;
+        let topology = self.start_state.get_topology();
+        self.heuristic.check_topology(topology)?;
+        // Pruning with an inadmissible heuristic would miss the shorter solutions
+        let heuristic = if self.heuristic.is_admissible(topology) {
+            self.heuristic
+        } else if topology == Topology::Plane
+            && self.goal.get_size() == 3
+            && !self.goal.is_partial()
+        {
+            Heuristic::Exact
+        } else {
+            Heuristic::admissible(&self.goal, topology)
+        };
+        let goal = heuristic.prepare_goal(&self.goal)?;
+        solutions::optimal_solutions(&self.start_state, &goal, heuristic, bound, cap)
+    }
+
+    pub fn start_state(&self) -> &Puzzle {
+        &self.start_state
+    }
+
     pub fn put_result(&self, output: Output) -> Result<()> {
         output.put_result(self.start_state.clone(), self.verbose)
     }
@@ -1,12 +1,17 @@
-use super::{ClosedSet, Heuristic, OpenSet, OpenSetNode, Output};
-use crate::{Move, Puzzle};
-use anyhow::Result;
+use super::checkpoint::{Resume, SearchState};
+use super::{
+    ClosedSet, Heuristic, OpenOptions, OpenSet, OpenSetNode, Output, SearchStats, Session, Timeout,
+};
+use crate::{Goal, Move, Puzzle};
+use anyhow::{anyhow, Result};
 use std::time::{Duration, Instant};
 
 fn append_optimal_state(
     open_set: &mut OpenSet,
     node: &OpenSetNode,
-    heuristic: fn(&Puzzle) -> usize,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
+    goal: &Goal,
+    stats: &mut SearchStats,
 ) -> Result<()> {
     let mut score = node.heuristics_cost();
     let mut optimal_puzzle = None;
@@ -14,7 +19,8 @@ fn append_optimal_state(
     for move_dir in Move::list() {
         let mut new_state = node.state().clone();
         if let Ok(()) = new_state.move_blank(move_dir) {
-            let new_score = heuristic(&new_state);
+            let new_score = heuristic(&new_state, goal);
+            stats.heuristic_evaluations += 1;
             if new_score < score {
                 score = new_score;
                 optimal_puzzle = Some(new_state);
@@ -30,7 +36,9 @@ fn append_optimal_state(
             new_path,
             node.moved_cost() + 1,
             heuristic,
+            goal,
         ));
+        stats.heuristic_evaluations += 1;
         Ok(())
     } else {
         Err(anyhow::anyhow!("Optimal state not found"))
@@ -41,20 +49,24 @@ fn append_all_movable_states(
     open_set: &mut OpenSet,
     closed_set: &ClosedSet,
     node: &OpenSetNode,
-    heuristic: fn(&Puzzle) -> usize,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
+    goal: &Goal,
+    stats: &mut SearchStats,
 ) {
+    let moved_cost = node.moved_cost() + 1;
     for move_dir in Move::list() {
         let mut new_state = node.state().clone();
         if let Ok(()) = new_state.move_blank(move_dir) {
-            if !closed_set.contains(&new_state) {
-                let mut new_path = node.path().clone();
-                new_path.push(move_dir);
-                open_set.insert(OpenSetNode::new(
-                    new_state,
-                    new_path,
-                    node.moved_cost() + 1,
-                    heuristic,
-                ));
+            match closed_set.get_moved_cost(&new_state) {
+                Some(cost) if cost <= moved_cost => stats.duplicates += 1,
+                _ => {
+                    let mut new_path = node.path().clone();
+                    new_path.push(move_dir);
+                    open_set.insert(OpenSetNode::new(
+                        new_state, new_path, moved_cost, heuristic, goal,
+                    ));
+                    stats.heuristic_evaluations += 1;
+                }
             }
         }
     }
@@ -62,43 +74,178 @@ fn append_all_movable_states(
 
 pub fn astar(
     puzzle: Puzzle,
-    heuristic: fn(&Puzzle) -> usize,
+    goal: &Goal,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
     is_greedy: bool,
     timeout: Option<u64>,
+    options: OpenOptions,
+    mut session: Session,
 ) -> Result<Output> {
-    let mut open_set = OpenSet::new();
-    let mut closed_set = ClosedSet::new();
-    open_set.insert(OpenSetNode::new(puzzle, vec![], 0, heuristic));
-    let mut max_size = 0;
+    let elapsed = session.elapsed();
+    let (mut open_set, mut closed_set, mut stats) = match session.resume.take() {
+        None => {
+            let mut open_set = OpenSet::new(options);
+            open_set.insert(OpenSetNode::new(puzzle, vec![], 0, heuristic, goal));
+            let stats = SearchStats {
+                heuristic_evaluations: 1,
+                ..Default::default()
+            };
+            (open_set, ClosedSet::new(), stats)
+        }
+        Some(Resume {
+            stats,
+            search:
+                SearchState::AStar {
+                    count,
+                    open,
+                    closed,
+                },
+            ..
+        }) => {
+            let mut closed_set = ClosedSet::new();
+            for (state, moved_cost) in closed {
+                closed_set.insert(state, moved_cost);
+            }
+            (OpenSet::restore(options, count, open), closed_set, stats)
+        }
+        Some(_) => {
+            return Err(anyhow!(
+                "The checkpoint was not written by an astar search."
+            ))
+        }
+    };
+    stats.update_peaks(open_set.len(), closed_set.len());
 
     let start = Instant::now();
     let timeout = timeout.map(|t| Duration::new(t, 0));
     while let Some(node) = open_set.pop() {
         if let Some(duration) = timeout {
             if start.elapsed() > duration {
-                return Err(anyhow::anyhow!("Timeout"));
+                return Err(Timeout.into());
+            }
+        }
+        // A state may be queued several times before being expanded
+        match closed_set.get_moved_cost(node.state()) {
+            Some(cost) if cost <= node.moved_cost() => {
+                stats.duplicates += 1;
+                continue;
             }
+            Some(_) => stats.reopened += 1,
+            None => {}
         }
-        if node.is_goal() {
-            return Ok(Output::new(
-                open_set.get_append_count(),
-                max_size,
-                start.elapsed().as_secs_f64(),
-                node.path().clone(),
-            ));
+        if node.is_goal(goal) {
+            stats.nodes_generated = open_set.get_append_count();
+            let elapsed = elapsed + start.elapsed().as_secs_f64();
+            let mut output = Output::new(stats, elapsed, node.path().clone());
+            output.tie_break = options.tie_break;
+            return Ok(output);
         }
+        stats.nodes_expanded += 1;
         if is_greedy {
-            append_optimal_state(&mut open_set, &node, heuristic)?;
+            append_optimal_state(&mut open_set, &node, heuristic, goal, &mut stats)?;
         } else {
-            append_all_movable_states(&mut open_set, &closed_set, &node, heuristic);
-            closed_set.insert(node.convert_to_state());
+            append_all_movable_states(
+                &mut open_set,
+                &closed_set,
+                &node,
+                heuristic,
+                goal,
+                &mut stats,
+            );
+            let moved_cost = node.moved_cost();
+            closed_set.insert(node.convert_to_state(), moved_cost);
+        }
+        stats.update_peaks(open_set.len(), closed_set.len());
+        if let Some(checkpointer) = &mut session.checkpointer {
+            if checkpointer.is_due() {
+                stats.nodes_generated = open_set.get_append_count();
+                let elapsed = elapsed + start.elapsed().as_secs_f64();
+                checkpointer.save_astar(&stats, elapsed, &open_set, &closed_set)?;
+            }
         }
-        // max_size <= open_set.len() + closed_set.len()
-        max_size = open_set.len() + closed_set.len();
     }
     Err(anyhow::anyhow!("No solution"))
 }
 
-pub(super) fn solve(puzzle: &Puzzle, heuristic: Heuristic, timeout: Option<u64>) -> Result<Output> {
-    astar(puzzle.clone(), heuristic.get_heuristic()?, false, timeout)
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    options: OpenOptions,
+    session: Session,
+) -> Result<Output> {
+    astar(
+        puzzle.clone(),
+        goal,
+        heuristic.get_heuristic()?,
+        false,
+        timeout,
+        options,
+        session,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::OpenList;
+
+    #[test]
+    fn test_search_stats() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 8, 3], vec![1, 6, 4], vec![7, 0, 5]])?;
+        let output = solve(
+            &puzzle,
+            &Goal::snail(3),
+            Heuristic::Manhattan,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )?;
+        let stats = output.stats;
+        assert_eq!(output.path.len(), 5);
+        assert_eq!(output.complexity_in_time, stats.nodes_generated);
+        assert_eq!(output.complexity_in_size, stats.max_memory);
+        assert_eq!(stats.heuristic_evaluations, stats.nodes_generated);
+        assert!(stats.nodes_expanded < stats.nodes_generated);
+        assert!(stats.max_memory >= stats.max_open.max(stats.max_closed));
+        assert!(stats.max_memory <= stats.max_open + stats.max_closed);
+        assert_eq!(stats.reopened, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_buckets_same_length_as_heap() -> Result<()> {
+        let goal = Goal::snail(3);
+        let buckets = OpenOptions {
+            list: OpenList::Buckets,
+            tie_break: None,
+        };
+        for _ in 0..5 {
+            let puzzle = Puzzle::generate_solvable_to(3, &goal)?;
+            let heap = solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                OpenOptions::default(),
+                Session::default(),
+            )?;
+            let output = solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                buckets,
+                Session::default(),
+            )?;
+            assert_eq!(output.path.len(), heap.path.len());
+            let mut state = puzzle.clone();
+            for m in &output.path {
+                state.move_blank(*m)?;
+            }
+            assert!(goal.is_reached(&state));
+        }
+        Ok(())
+    }
 }
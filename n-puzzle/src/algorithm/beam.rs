@@ -0,0 +1,167 @@
+use super::{Heuristic, Output, SearchStats, Timeout};
+use crate::{Goal, Move, Puzzle, ZobristBuildHasher};
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// States kept at every depth by default
+pub const DEFAULT_BEAM_WIDTH: usize = 1000;
+
+/// How a state of a layer was reached from the previous layer
+struct Step {
+    parent: usize,
+    m: Move,
+}
+
+fn path(layers: &[Vec<Step>], mut index: usize) -> Vec<Move> {
+    let mut path = Vec::with_capacity(layers.len());
+    for layer in layers.iter().rev() {
+        path.push(layer[index].m);
+        index = layer[index].parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth-first search keeping only the `width` states of lowest heuristic at every depth.
+///
+/// A state already kept at a previous depth, or twice in the same layer, is dropped. The search is fast,
+/// but neither complete nor optimal: it fails when every state of a layer is dropped.
+/// Only `width` states are expanded at once, yet the key and the move of every kept state are remembered,
+/// so the memory grows by up to `width` entries at every depth.
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    width: usize,
+    timeout: Option<u64>,
+) -> Result<Output> {
+    let heuristic = heuristic.get_heuristic()?;
+    let start = Instant::now();
+    let timeout = timeout.map(|t| Duration::new(t, 0));
+    let mut stats = SearchStats {
+        nodes_generated: 1,
+        ..Default::default()
+    };
+    if goal.is_reached(puzzle) {
+        return Ok(Output::new(
+            stats,
+            start.elapsed().as_secs_f64(),
+            Vec::new(),
+        ));
+    }
+    // Only the keys of the states are kept, as a collision between 64-bit keys is negligible
+    let mut seen: HashSet<u64, ZobristBuildHasher> = HashSet::default();
+    seen.insert(puzzle.zobrist_key());
+    let mut beam = vec![puzzle.clone()];
+    let mut layers: Vec<Vec<Step>> = Vec::new();
+    loop {
+        let mut layer: HashSet<u64, ZobristBuildHasher> = HashSet::default();
+        let mut candidates = Vec::new();
+        for (parent, state) in beam.iter().enumerate() {
+            if let Some(duration) = timeout {
+                if start.elapsed() > duration {
+                    return Err(Timeout.into());
+                }
+            }
+            stats.nodes_expanded += 1;
+            for m in Move::list() {
+                let mut next = state.clone();
+                if next.move_blank(m).is_err() {
+                    continue;
+                }
+                let key = next.zobrist_key();
+                if seen.contains(&key) || !layer.insert(key) {
+                    stats.duplicates += 1;
+                    continue;
+                }
+                stats.nodes_generated += 1;
+                if goal.is_reached(&next) {
+                    layers.push(vec![Step { parent, m }]);
+                    return Ok(Output::new(
+                        stats,
+                        start.elapsed().as_secs_f64(),
+                        path(&layers, 0),
+                    ));
+                }
+                stats.heuristic_evaluations += 1;
+                candidates.push((heuristic(&next, goal), next, Step { parent, m }));
+            }
+        }
+        // The sort is stable, so that the search is the same in every run
+        candidates.sort_by_key(|(estimate, _, _)| *estimate);
+        candidates.truncate(width);
+        if candidates.is_empty() {
+            return Err(anyhow!(
+                "The beam died out at depth {} without reaching the goal",
+                layers.len() + 1
+            ));
+        }
+        let mut steps = Vec::with_capacity(candidates.len());
+        beam.clear();
+        for (_, state, step) in candidates {
+            seen.insert(state.zobrist_key());
+            beam.push(state);
+            steps.push(step);
+        }
+        layers.push(steps);
+        stats.update_peaks(beam.len(), seen.len() - beam.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{astar, OpenOptions, Session};
+
+    fn check(puzzle: &Puzzle, goal: &Goal, width: usize) -> Result<Output> {
+        let output = solve(puzzle, goal, Heuristic::Manhattan, width, None)?;
+        let mut state = puzzle.clone();
+        for m in &output.path {
+            state.move_blank(*m)?;
+        }
+        assert!(goal.is_reached(&state));
+        assert!(output.stats.max_open <= width);
+        Ok(output)
+    }
+
+    #[test]
+    fn test_beam() -> Result<()> {
+        for size in 3..=4 {
+            let goal = Goal::snail(size);
+            for _ in 0..3 {
+                check(&Puzzle::generate_solvable_to(size, &goal)?, &goal, 1000)?;
+            }
+        }
+        let goal = Goal::snail(3);
+        check(goal.target(), &goal, 1)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_wide_is_optimal() -> Result<()> {
+        // A beam holding every state of a depth is a breadth-first search
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![8, 7, 6], vec![1, 0, 5], vec![2, 3, 4]])?;
+        let output = check(&puzzle, &goal, 200_000)?;
+        let expected = astar::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )?;
+        assert_eq!(output.path.len(), expected.path.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_dies_out() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![8, 7, 6], vec![1, 0, 5], vec![2, 3, 4]])?;
+        let error = solve(&puzzle, &goal, Heuristic::Manhattan, 1, None).unwrap_err();
+        assert!(error.to_string().starts_with("The beam died out at depth"));
+        Ok(())
+    }
+}
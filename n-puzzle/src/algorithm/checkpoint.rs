@@ -0,0 +1,733 @@
+use super::{
+    Algorithm, ClosedSet, Heuristic, OpenList, OpenOptions, OpenSet, OpenSetNode, SearchStats,
+    TieBreak,
+};
+use crate::{Goal, Move, Pos, Puzzle, Topology};
+use anyhow::{anyhow, Result};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Seconds between two checkpoints by default
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 300;
+const FILE_MAGIC: &[u8] = b"n-puzzle checkpoint ";
+const FILE_HEADER: &[u8] = b"n-puzzle checkpoint 2\n";
+const WILDCARD: u64 = u64::MAX;
+const NONE: u64 = u64::MAX;
+
+/// The algorithms whose state can be saved
+const ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::AStar,
+    Algorithm::UniformCost,
+    Algorithm::Greedy,
+    Algorithm::IdaStar,
+];
+const HEURISTICS: [Heuristic; 6] = [
+    Heuristic::Manhattan,
+    Heuristic::Hamming,
+    Heuristic::LinearConflict,
+    Heuristic::InversionDistance,
+    Heuristic::Exact,
+    Heuristic::None,
+];
+
+/// The search a checkpoint belongs to
+#[derive(Debug, Clone)]
+pub(super) struct Header {
+    pub(super) algorithm: Algorithm,
+    pub(super) heuristic: Heuristic,
+    pub(super) start: Puzzle,
+    pub(super) goal: Goal,
+    pub(super) open_options: OpenOptions,
+}
+
+/// Where a search stopped
+pub(super) enum SearchState {
+    AStar {
+        /// Nodes inserted in the open set so far
+        count: usize,
+        open: Vec<OpenSetNode>,
+        closed: Vec<(Puzzle, usize)>,
+    },
+    IdaStar {
+        bound: usize,
+        /// Lowest cost beyond the bound found so far in the iteration
+        next_bound: Option<usize>,
+        /// Path to the next state to expand: every subtree before it was searched with this bound
+        path: Vec<Move>,
+    },
+}
+
+/// The state a search resumes from
+pub(super) struct Resume {
+    pub(super) stats: SearchStats,
+    /// Seconds already spent by the search
+    pub(super) elapsed: f64,
+    pub(super) search: SearchState,
+}
+
+/// Checkpoints written by a search, and the checkpoint it resumes from
+#[derive(Default)]
+pub(super) struct Session {
+    pub(super) checkpointer: Option<Checkpointer>,
+    pub(super) resume: Option<Resume>,
+}
+
+impl Session {
+    /// Seconds already spent by the search before this session
+    pub(super) fn elapsed(&self) -> f64 {
+        self.resume.as_ref().map_or(0.0, |resume| resume.elapsed)
+    }
+}
+
+fn move_code(m: Move) -> u8 {
+    match m {
+        Move::Up => 0,
+        Move::Down => 1,
+        Move::Left => 2,
+        Move::Right => 3,
+    }
+}
+
+struct Encoder {
+    writer: BufWriter<File>,
+}
+
+impl Encoder {
+    fn u8(&mut self, value: u8) -> Result<()> {
+        self.writer.write_all(&[value])?;
+        Ok(())
+    }
+
+    fn u64(&mut self, value: u64) -> Result<()> {
+        self.writer.write_all(&value.to_le_bytes())?;
+        Ok(())
+    }
+
+    fn usize(&mut self, value: usize) -> Result<()> {
+        self.u64(value as u64)
+    }
+
+    fn option(&mut self, value: Option<usize>) -> Result<()> {
+        self.u64(value.map_or(NONE, |value| value as u64))
+    }
+
+    fn str(&mut self, value: &str) -> Result<()> {
+        self.usize(value.len())?;
+        self.writer.write_all(value.as_bytes())?;
+        Ok(())
+    }
+
+    /// Cells in reading order, two bytes each since boards hold fewer than 65536 cells
+    fn puzzle(&mut self, puzzle: &Puzzle) -> Result<()> {
+        let size = puzzle.get_size();
+        for i in 0..size * size {
+            let value = puzzle.get(Pos::new(i % size, i / size))? as u16;
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn path(&mut self, path: &[Move]) -> Result<()> {
+        self.usize(path.len())?;
+        for &m in path {
+            self.u8(move_code(m))?;
+        }
+        Ok(())
+    }
+
+    fn stats(&mut self, stats: &SearchStats) -> Result<()> {
+        for value in [
+            stats.nodes_generated,
+            stats.nodes_expanded,
+            stats.duplicates,
+            stats.reopened,
+            stats.max_open,
+            stats.max_closed,
+            stats.max_memory,
+            stats.heuristic_evaluations,
+        ] {
+            self.usize(value)?;
+        }
+        Ok(())
+    }
+
+    fn header(&mut self, header: &Header) -> Result<()> {
+        self.writer.write_all(FILE_HEADER)?;
+        self.str(&header.algorithm.to_string())?;
+        self.str(&header.heuristic.to_string())?;
+        let size = header.start.get_size();
+        self.usize(size)?;
+        self.u8(match header.start.get_topology() {
+            Topology::Plane => 0,
+            Topology::Torus => 1,
+        })?;
+        self.puzzle(&header.start)?;
+        let target = header.goal.target();
+        for i in 0..size * size {
+            let value = target.get(Pos::new(i % size, i / size))?;
+            if header.goal.is_fixed(value) {
+                self.usize(value)?;
+            } else {
+                self.u64(WILDCARD)?;
+            }
+        }
+        self.u8(match header.open_options.list {
+            OpenList::Heap => 0,
+            OpenList::Buckets => 1,
+        })?;
+        let (tie_break, seed) = match header.open_options.tie_break {
+            None => (0, 0),
+            Some(TieBreak::HigherG) => (1, 0),
+            Some(TieBreak::LowerH) => (2, 0),
+            Some(TieBreak::Lifo) => (3, 0),
+            Some(TieBreak::Fifo) => (4, 0),
+            Some(TieBreak::Random(seed)) => (5, seed),
+        };
+        self.u8(tie_break)?;
+        self.u64(seed)
+    }
+}
+
+struct Decoder {
+    reader: BufReader<File>,
+    path: String,
+}
+
+impl Decoder {
+    fn open(path: &str) -> Result<Self> {
+        let file =
+            File::open(path).map_err(|e| anyhow!("Cannot open the checkpoint {}: {}", path, e))?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            path: path.to_string(),
+        })
+    }
+
+    fn invalid(&self) -> anyhow::Error {
+        anyhow!("Not a valid checkpoint file: {}", self.path)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; len];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|_| self.invalid())?;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|_| self.invalid())?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        Ok(self.u64()? as usize)
+    }
+
+    fn option(&mut self) -> Result<Option<usize>> {
+        Ok(match self.u64()? {
+            NONE => None,
+            value => Some(value as usize),
+        })
+    }
+
+    /// Length of a sequence, which cannot hold more elements than the file has bytes
+    fn len(&mut self) -> Result<usize> {
+        let len = self.usize()?;
+        let file_len = self.reader.get_ref().metadata()?.len() as usize;
+        if len > file_len {
+            return Err(self.invalid());
+        }
+        Ok(len)
+    }
+
+    fn str(&mut self) -> Result<String> {
+        let len = self.len()?;
+        String::from_utf8(self.bytes(len)?).map_err(|_| self.invalid())
+    }
+
+    fn puzzle(&mut self, size: usize, topology: Topology) -> Result<Puzzle> {
+        let mut state = vec![vec![0; size]; size];
+        for row in state.iter_mut() {
+            for cell in row.iter_mut() {
+                let mut bytes = [0; 2];
+                self.reader
+                    .read_exact(&mut bytes)
+                    .map_err(|_| self.invalid())?;
+                *cell = u16::from_le_bytes(bytes) as usize;
+            }
+        }
+        let mut puzzle = Puzzle::new_from_state(state).map_err(|_| self.invalid())?;
+        puzzle.set_topology(topology);
+        Ok(puzzle)
+    }
+
+    fn path(&mut self) -> Result<Vec<Move>> {
+        let len = self.len()?;
+        let moves = Move::list();
+        self.bytes(len)?
+            .into_iter()
+            .map(|code| {
+                moves
+                    .get(code as usize)
+                    .copied()
+                    .ok_or_else(|| self.invalid())
+            })
+            .collect()
+    }
+
+    fn stats(&mut self) -> Result<SearchStats> {
+        Ok(SearchStats {
+            nodes_generated: self.usize()?,
+            nodes_expanded: self.usize()?,
+            duplicates: self.usize()?,
+            reopened: self.usize()?,
+            max_open: self.usize()?,
+            max_closed: self.usize()?,
+            max_memory: self.usize()?,
+            heuristic_evaluations: self.usize()?,
+        })
+    }
+
+    fn header(&mut self) -> Result<Header> {
+        let magic = self.bytes(FILE_MAGIC.len())?;
+        if magic != FILE_MAGIC {
+            return Err(self.invalid());
+        }
+        let mut version = FILE_MAGIC.to_vec();
+        while version.last() != Some(&b'\n') && version.len() < FILE_HEADER.len() + 8 {
+            version.push(self.u8()?);
+        }
+        if version != FILE_HEADER {
+            return Err(anyhow!(
+                "The checkpoint {} was written by another version of n-puzzle: {}",
+                self.path,
+                String::from_utf8_lossy(&version[FILE_MAGIC.len()..]).trim()
+            ));
+        }
+        let algorithm = self.str()?;
+        let algorithm = ALGORITHMS
+            .into_iter()
+            .find(|candidate| candidate.to_string() == algorithm)
+            .ok_or_else(|| self.invalid())?;
+        let heuristic = self.str()?;
+        let heuristic = HEURISTICS
+            .into_iter()
+            .find(|candidate| candidate.to_string() == heuristic)
+            .ok_or_else(|| self.invalid())?;
+        let size = self.usize()?;
+        if !(2..=crate::MAX_PUZZLE_SIZE).contains(&size) {
+            return Err(self.invalid());
+        }
+        let topology = match self.u8()? {
+            0 => Topology::Plane,
+            1 => Topology::Torus,
+            _ => return Err(self.invalid()),
+        };
+        let start = self.puzzle(size, topology)?;
+        let mut cells = Vec::with_capacity(size * size);
+        for _ in 0..size * size {
+            cells.push(match self.u64()? {
+                WILDCARD => None,
+                value => Some(value as usize),
+            });
+        }
+        let goal = Goal::from_cells(size, &cells).map_err(|_| self.invalid())?;
+        let list = match self.u8()? {
+            0 => OpenList::Heap,
+            1 => OpenList::Buckets,
+            _ => return Err(self.invalid()),
+        };
+        let (tie_break, seed) = (self.u8()?, self.u64()?);
+        let tie_break = match tie_break {
+            0 => None,
+            1 => Some(TieBreak::HigherG),
+            2 => Some(TieBreak::LowerH),
+            3 => Some(TieBreak::Lifo),
+            4 => Some(TieBreak::Fifo),
+            5 => Some(TieBreak::Random(seed)),
+            _ => return Err(self.invalid()),
+        };
+        Ok(Header {
+            algorithm,
+            heuristic,
+            start,
+            goal,
+            open_options: OpenOptions { list, tie_break },
+        })
+    }
+
+    /// The open nodes are scored against `goal`, the goal of the header prepared for its heuristic
+    fn resume(&mut self, header: &Header, goal: &Goal) -> Result<Resume> {
+        let stats = self.stats()?;
+        let elapsed = f64::from_bits(self.u64()?);
+        let (size, topology) = (header.start.get_size(), header.start.get_topology());
+        let search = match self.u8()? {
+            0 => {
+                let heuristic = match header.algorithm {
+                    Algorithm::UniformCost => super::uniform_cost::uniform_cost,
+                    _ => header.heuristic.get_heuristic()?,
+                };
+                let count = self.usize()?;
+                let mut open = Vec::new();
+                for _ in 0..self.len()? {
+                    let state = self.puzzle(size, topology)?;
+                    let path = self.path()?;
+                    let (moved_cost, tie) = (self.usize()?, self.u64()?);
+                    let node = OpenSetNode::new(state, path, moved_cost, heuristic, goal);
+                    open.push(node.with_tie(tie));
+                }
+                let mut closed = Vec::new();
+                for _ in 0..self.len()? {
+                    closed.push((self.puzzle(size, topology)?, self.usize()?));
+                }
+                SearchState::AStar {
+                    count,
+                    open,
+                    closed,
+                }
+            }
+            1 => SearchState::IdaStar {
+                bound: self.usize()?,
+                next_bound: self.option()?,
+                path: self.path()?,
+            },
+            _ => return Err(self.invalid()),
+        };
+        Ok(Resume {
+            stats,
+            elapsed,
+            search,
+        })
+    }
+}
+
+/// The search saved in the checkpoint at `path`
+pub(super) fn read_header(path: &str) -> Result<Header> {
+    Decoder::open(path)?.header()
+}
+
+/// Where the search saved in the checkpoint at `path` stopped,
+/// with the open nodes scored against the `goal` the resumed search uses
+pub(super) fn read_resume(path: &str, goal: &Goal) -> Result<Resume> {
+    let mut decoder = Decoder::open(path)?;
+    let header = decoder.header()?;
+    decoder.resume(&header, goal)
+}
+
+/// Writes the state of a search to a file at a fixed interval.
+/// The file is replaced at once, so that a search killed while writing keeps its previous checkpoint.
+pub(super) struct Checkpointer {
+    path: String,
+    interval: Duration,
+    last: Instant,
+    header: Header,
+}
+
+impl Checkpointer {
+    pub(super) fn new(path: &str, interval: Duration, header: Header) -> Self {
+        Self {
+            path: path.to_string(),
+            interval,
+            last: Instant::now(),
+            header,
+        }
+    }
+
+    pub(super) fn is_due(&self) -> bool {
+        self.last.elapsed() >= self.interval
+    }
+
+    fn save(
+        &mut self,
+        stats: &SearchStats,
+        elapsed: f64,
+        search: impl FnOnce(&mut Encoder) -> Result<()>,
+    ) -> Result<()> {
+        let tmp_path = format!("{}.tmp", self.path);
+        let mut encoder = Encoder {
+            writer: BufWriter::new(File::create(&tmp_path)?),
+        };
+        encoder.header(&self.header)?;
+        encoder.stats(stats)?;
+        encoder.u64(elapsed.to_bits())?;
+        search(&mut encoder)?;
+        encoder.writer.flush()?;
+        drop(encoder);
+        fs::rename(&tmp_path, &self.path)?;
+        self.last = Instant::now();
+        Ok(())
+    }
+
+    pub(super) fn save_astar(
+        &mut self,
+        stats: &SearchStats,
+        elapsed: f64,
+        open_set: &OpenSet,
+        closed_set: &ClosedSet,
+    ) -> Result<()> {
+        self.save(stats, elapsed, |encoder| {
+            encoder.u8(0)?;
+            encoder.usize(open_set.get_append_count())?;
+            encoder.usize(open_set.len())?;
+            for node in open_set.iter() {
+                encoder.puzzle(node.state())?;
+                encoder.path(node.path())?;
+                encoder.usize(node.moved_cost())?;
+                encoder.u64(node.tie())?;
+            }
+            encoder.usize(closed_set.len())?;
+            for (state, moved_cost) in closed_set.iter() {
+                encoder.puzzle(state)?;
+                encoder.usize(moved_cost)?;
+            }
+            Ok(())
+        })
+    }
+
+    pub(super) fn save_ida_star(
+        &mut self,
+        stats: &SearchStats,
+        elapsed: f64,
+        bound: usize,
+        next_bound: Option<usize>,
+        path: &[Move],
+    ) -> Result<()> {
+        self.save(stats, elapsed, |encoder| {
+            encoder.u8(1)?;
+            encoder.usize(bound)?;
+            encoder.option(next_bound)?;
+            encoder.path(path)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{astar, ida_star};
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("n_puzzle_test_checkpoint_{}.bin", name));
+        path.to_string_lossy().to_string()
+    }
+
+    fn header(algorithm: Algorithm, puzzle: &Puzzle, goal: &Goal) -> Header {
+        Header {
+            algorithm,
+            heuristic: Heuristic::Manhattan,
+            start: puzzle.clone(),
+            goal: goal.clone(),
+            open_options: OpenOptions::default(),
+        }
+    }
+
+    /// Session saving a checkpoint at every expansion
+    fn saving(path: &str, header: Header) -> Session {
+        Session {
+            checkpointer: Some(Checkpointer::new(path, Duration::ZERO, header)),
+            resume: None,
+        }
+    }
+
+    fn resuming(path: &str, goal: &Goal) -> Result<Session> {
+        Ok(Session {
+            checkpointer: None,
+            resume: Some(read_resume(path, goal)?),
+        })
+    }
+
+    #[test]
+    fn test_resume_astar() -> Result<()> {
+        let path = temp_path("astar");
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![3, 1, 2], vec![8, 0, 4], vec![7, 6, 5]])?;
+        let options = OpenOptions {
+            list: OpenList::Buckets,
+            tie_break: None,
+        };
+        let mut header = header(Algorithm::AStar, &puzzle, &goal);
+        header.open_options = options;
+        let session = saving(&path, header);
+        let output = astar::solve(&puzzle, &goal, Heuristic::Manhattan, None, options, session)?;
+        let saved = read_header(&path)?;
+        assert_eq!(saved.algorithm, Algorithm::AStar);
+        assert_eq!(saved.start, puzzle);
+        assert_eq!(saved.goal, goal);
+        assert_eq!(saved.open_options, options);
+        let resume = read_resume(&path, &goal)?;
+        match &resume.search {
+            SearchState::AStar { open, closed, .. } => {
+                assert!(!open.is_empty());
+                assert_eq!(closed.len(), resume.stats.nodes_expanded);
+            }
+            _ => panic!("not an astar checkpoint"),
+        }
+        // The last checkpoint was saved just before the goal was reached
+        let resumed = astar::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            options,
+            resuming(&path, &goal)?,
+        )?;
+        assert_eq!(resumed.path.len(), output.path.len());
+        assert_eq!(resumed.stats.nodes_expanded, output.stats.nodes_expanded);
+        assert!(ida_star::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            None,
+            resuming(&path, &goal)?
+        )
+        .is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_exact() -> Result<()> {
+        let path = temp_path("exact");
+        let goal = Heuristic::Exact.prepare_goal(&Goal::snail(3))?;
+        let puzzle = Puzzle::new_from_state(vec![vec![8, 7, 6], vec![1, 0, 5], vec![2, 3, 4]])?;
+        let mut header = header(Algorithm::AStar, &puzzle, &goal);
+        header.heuristic = Heuristic::Exact;
+        let session = saving(&path, header);
+        let options = OpenOptions::default();
+        let output = astar::solve(&puzzle, &goal, Heuristic::Exact, None, options, session)?;
+        // The open nodes are scored with the exact distances, as before the checkpoint
+        let resume = read_resume(&path, &goal)?;
+        match &resume.search {
+            SearchState::AStar { open, .. } => {
+                assert!(open.iter().all(
+                    |node| node.heuristics_cost() == crate::oracle::exact(node.state(), &goal)
+                ));
+            }
+            _ => panic!("not an astar checkpoint"),
+        }
+        let resumed = astar::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Exact,
+            None,
+            options,
+            resuming(&path, &goal)?,
+        )?;
+        assert_eq!(resumed.path, output.path);
+        assert_eq!(resumed.stats.nodes_expanded, output.stats.nodes_expanded);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_ida_star() -> Result<()> {
+        let path = temp_path("ida_star");
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![8, 7, 6], vec![1, 0, 5], vec![2, 3, 4]])?;
+        let session = saving(&path, header(Algorithm::IdaStar, &puzzle, &goal));
+        let output = ida_star::solve(&puzzle, &goal, Heuristic::Manhattan, None, None, session)?;
+        let resume = read_resume(&path, &goal)?;
+        // Manhattan overestimates, so the last bound can be past the length of the solution
+        let bound = match &resume.search {
+            SearchState::IdaStar { bound, path, .. } => {
+                assert!(*bound >= output.path.len());
+                assert_eq!(path[..], output.path[..output.path.len() - 1]);
+                *bound
+            }
+            _ => panic!("not an ida_star checkpoint"),
+        };
+        let resumed = ida_star::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            None,
+            resuming(&path, &goal)?,
+        )?;
+        assert_eq!(resumed.path, output.path);
+
+        // Resuming anywhere on the way to the solution skips the subtrees searched before it
+        for depth in [0, 5, 12] {
+            let mut encoder = Encoder {
+                writer: BufWriter::new(File::create(&path)?),
+            };
+            encoder.header(&header(Algorithm::IdaStar, &puzzle, &goal))?;
+            encoder.stats(&SearchStats::default())?;
+            encoder.u64(0.0f64.to_bits())?;
+            encoder.u8(1)?;
+            encoder.usize(bound)?;
+            encoder.option(None)?;
+            encoder.path(&output.path[..depth])?;
+            encoder.writer.flush()?;
+            drop(encoder);
+            let resumed = ida_star::solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                None,
+                resuming(&path, &goal)?,
+            )?;
+            assert_eq!(resumed.path, output.path);
+            assert!(resumed.stats.nodes_expanded < output.stats.nodes_expanded);
+        }
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_resume_torus() -> Result<()> {
+        let path = temp_path("torus");
+        let goal = Goal::snail(3);
+        let mut puzzle = Puzzle::new_from_state(vec![vec![5, 2, 4], vec![8, 1, 6], vec![0, 3, 7]])?;
+        puzzle.set_topology(Topology::Torus);
+        let session = saving(&path, header(Algorithm::AStar, &puzzle, &goal));
+        let options = OpenOptions::default();
+        let output = astar::solve(&puzzle, &goal, Heuristic::Manhattan, None, options, session)?;
+        assert_eq!(read_header(&path)?.start.get_topology(), Topology::Torus);
+        let resumed = astar::solve(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            options,
+            resuming(&path, &goal)?,
+        )?;
+        assert_eq!(resumed.path.len(), output.path.len());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_checkpoint() -> Result<()> {
+        let path = temp_path("invalid");
+        fs::write(&path, b"not a checkpoint")?;
+        assert!(read_header(&path)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Not a valid checkpoint file"));
+        fs::write(&path, b"n-puzzle checkpoint 0\nastar")?;
+        assert!(read_header(&path)
+            .unwrap_err()
+            .to_string()
+            .contains("another version"));
+        let mut bytes = FILE_HEADER.to_vec();
+        bytes.extend_from_slice(&5u64.to_le_bytes());
+        bytes.extend_from_slice(b"astar");
+        fs::write(&path, bytes)?;
+        assert!(read_header(&path).is_err());
+        fs::remove_file(&path)?;
+        assert!(read_header(&path).is_err());
+        Ok(())
+    }
+}
@@ -1,23 +1,31 @@
-use crate::Puzzle;
-use std::collections::HashSet;
+use crate::{Puzzle, ZobristBuildHasher};
+use std::collections::HashMap;
 
+/// Expanded states with the cost at which they were reached
 pub struct ClosedSet {
-    set: HashSet<Puzzle>,
+    /// Hashed by the Zobrist key of the states
+    set: HashMap<Puzzle, usize, ZobristBuildHasher>,
 }
 
 impl ClosedSet {
     pub fn new() -> Self {
         Self {
-            set: HashSet::new(),
+            set: HashMap::default(),
         }
     }
 
-    pub fn insert(&mut self, state: Puzzle) {
-        self.set.insert(state);
+    pub fn insert(&mut self, state: Puzzle, moved_cost: usize) {
+        self.set.insert(state, moved_cost);
     }
 
-    pub fn contains(&self, state: &Puzzle) -> bool {
-        self.set.contains(state)
+    pub fn get_moved_cost(&self, state: &Puzzle) -> Option<usize> {
+        self.set.get(state).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Puzzle, usize)> {
+        self.set
+            .iter()
+            .map(|(state, &moved_cost)| (state, moved_cost))
     }
 
     pub fn len(&self) -> usize {
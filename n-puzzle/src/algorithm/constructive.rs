@@ -0,0 +1,391 @@
+use super::{Output, SearchStats, Timeout};
+use crate::{Goal, Move, Pos, Puzzle};
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Places the tiles one by one, locking every placed tile.
+///
+/// The unsolved part of the board is a rectangle. Its edge lines that do not hold the goal blank
+/// are placed one after the other, the larger side first, which goes ring by ring for the snail goal,
+/// until a 2x2 block is left and rotated into place.
+/// The hierarchical solver stops earlier and searches the remaining block.
+pub(super) struct Builder {
+    size: usize,
+    /// Value of every cell in reading order
+    cells: Vec<usize>,
+    /// Cell index of every value
+    positions: Vec<usize>,
+    locked: Vec<bool>,
+    path: Vec<Move>,
+    /// Breadth-first search buffers, reused between searches
+    visited: Vec<u32>,
+    stamp: u32,
+    previous: Vec<(usize, Move)>,
+}
+
+impl Builder {
+    pub(super) fn new(puzzle: &Puzzle) -> Result<Self> {
+        let size = puzzle.get_size();
+        let mut cells = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                cells.push(puzzle.get(Pos::new(x, y))?);
+            }
+        }
+        let mut positions = vec![0; size * size];
+        for (index, &value) in cells.iter().enumerate() {
+            positions[value] = index;
+        }
+        Ok(Self {
+            size,
+            cells,
+            positions,
+            locked: vec![false; size * size],
+            path: Vec::new(),
+            visited: vec![0; size * size],
+            stamp: 0,
+            previous: vec![(0, Move::Up); size * size],
+        })
+    }
+
+    fn index(&self, pos: Pos) -> usize {
+        pos.y * self.size + pos.x
+    }
+
+    /// Neighbor cell in the direction of the move, if it is on the board
+    fn step(&self, index: usize, m: Move) -> Option<usize> {
+        let (x, y) = (index % self.size, index / self.size);
+        match m {
+            Move::Up if y > 0 => Some(index - self.size),
+            Move::Down if y + 1 < self.size => Some(index + self.size),
+            Move::Left if x > 0 => Some(index - 1),
+            Move::Right if x + 1 < self.size => Some(index + 1),
+            _ => None,
+        }
+    }
+
+    /// Shortest moves from `from` to `to` through unlocked cells other than `avoid`
+    fn route(&mut self, from: usize, to: usize, avoid: Option<usize>) -> Option<Vec<Move>> {
+        self.stamp += 1;
+        let stamp = self.stamp;
+        self.visited[from] = stamp;
+        let mut queue = VecDeque::from([from]);
+        while let Some(index) = queue.pop_front() {
+            if index == to {
+                let mut moves = Vec::new();
+                let mut current = to;
+                while current != from {
+                    let (previous, m) = self.previous[current];
+                    moves.push(m);
+                    current = previous;
+                }
+                moves.reverse();
+                return Some(moves);
+            }
+            for m in Move::list() {
+                if let Some(next) = self.step(index, m) {
+                    if self.visited[next] != stamp && !self.locked[next] && Some(next) != avoid {
+                        self.visited[next] = stamp;
+                        self.previous[next] = (index, m);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn apply(&mut self, m: Move) -> Result<()> {
+        let blank = self.positions[0];
+        let target = self
+            .step(blank, m)
+            .ok_or_else(|| anyhow!("Cannot move {:?}", m))?;
+        let value = self.cells[target];
+        self.cells.swap(blank, target);
+        self.positions[value] = blank;
+        self.positions[0] = target;
+        self.path.push(m);
+        Ok(())
+    }
+
+    fn move_blank_to(&mut self, to: usize, avoid: Option<usize>) -> Result<()> {
+        let moves = self
+            .route(self.positions[0], to, avoid)
+            .ok_or_else(|| anyhow!("The constructive solver cannot reach a cell"))?;
+        for m in moves {
+            self.apply(m)?;
+        }
+        Ok(())
+    }
+
+    /// Move a tile along a shortest route, bringing the blank in front of it at every step
+    fn move_tile(&mut self, value: usize, to: usize) -> Result<()> {
+        let from = self.positions[value];
+        let moves = self
+            .route(from, to, None)
+            .ok_or_else(|| anyhow!("The constructive solver cannot move a tile"))?;
+        let mut tile = from;
+        for m in moves {
+            let next = self.step(tile, m).unwrap();
+            self.move_blank_to(next, Some(tile))?;
+            self.apply(m.opposite())?;
+            tile = next;
+        }
+        Ok(())
+    }
+
+    /// Place the goal values of a line of cells, the moves from the last but one cell
+    /// to the last cell being `along`, and the unsolved cells lying in direction `inward`
+    fn solve_line(&mut self, goal: &Goal, line: &[usize], along: Move, inward: Move) -> Result<()> {
+        let size = self.size;
+        let value = |index: usize| goal.target().get(Pos::new(index % size, index / size));
+        let length = line.len();
+        for &cell in &line[..length - 2] {
+            self.move_tile(value(cell)?, cell)?;
+            self.locked[cell] = true;
+        }
+        let (last_but_one, last) = (line[length - 2], line[length - 1]);
+        let (first_value, last_value) = (value(last_but_one)?, value(last)?);
+        if self.positions[first_value] != last_but_one || self.positions[last_value] != last {
+            // Park both tiles at the end of the line, then slide them in with the blank
+            let below = self.step(last, inward).unwrap();
+            self.move_tile(first_value, last)?;
+            self.locked[last] = true;
+            let block = self.end_block(last_but_one, last, inward);
+            if block.contains(&self.positions[last_value]) {
+                // The blank may not get around the tile near the corner: search the block instead
+                let tile = self.positions[last_value];
+                if !block.contains(&self.positions[0]) {
+                    let free = *block[2..].iter().find(|&&cell| cell != tile).unwrap();
+                    self.move_blank_to(free, Some(tile))?;
+                }
+                self.locked[last] = false;
+                self.solve_corner(first_value, last_value, &block)?;
+            } else {
+                self.move_tile(last_value, below)?;
+                self.locked[below] = true;
+                self.move_blank_to(last_but_one, None)?;
+                self.locked[below] = false;
+                self.apply(along)?;
+                self.apply(inward)?;
+            }
+        }
+        self.locked[last_but_one] = true;
+        self.locked[last] = true;
+        Ok(())
+    }
+
+    /// The 2x3 block at the end of a line: the last two cells of the line first,
+    /// then the two cells inward of them, then the next two
+    fn end_block(&self, last_but_one: usize, last: usize, inward: Move) -> Vec<usize> {
+        let mut block = vec![last_but_one, last];
+        for i in 0..4 {
+            block.push(self.step(block[i], inward).unwrap());
+        }
+        block
+    }
+
+    /// Bring two tiles to the first two cells of the block with a breadth-first search
+    /// over the positions of the two tiles and the blank, which has to be in the block already
+    fn solve_corner(
+        &mut self,
+        first_value: usize,
+        last_value: usize,
+        block: &[usize],
+    ) -> Result<()> {
+        let start = (
+            self.positions[first_value],
+            self.positions[last_value],
+            self.positions[0],
+        );
+        let mut previous = HashMap::from([(start, None)]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(state) = queue.pop_front() {
+            let (first, second, blank) = state;
+            if first == block[0] && second == block[1] {
+                let mut moves = Vec::new();
+                let mut current = state;
+                while let Some((before, m)) = previous[&current] {
+                    moves.push(m);
+                    current = before;
+                }
+                for m in moves.into_iter().rev() {
+                    self.apply(m)?;
+                }
+                return Ok(());
+            }
+            for m in Move::list() {
+                let next_blank = match self.step(blank, m) {
+                    Some(next) if block.contains(&next) => next,
+                    _ => continue,
+                };
+                let moved = |cell: usize| if cell == next_blank { blank } else { cell };
+                let next = (moved(first), moved(second), next_blank);
+                if let std::collections::hash_map::Entry::Vacant(entry) = previous.entry(next) {
+                    entry.insert(Some((state, m)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        Err(anyhow!(
+            "The constructive solver cannot place the end of a line"
+        ))
+    }
+
+    /// Rotate the last three tiles of a 2x2 block into place
+    fn solve_block(&mut self, goal: &Goal, left: usize, top: usize) -> Result<()> {
+        let corner = self.index(Pos::new(left, top));
+        // Cells of the block in clockwise order and the moves of the blank between them
+        let cycle = [
+            (corner, Move::Right),
+            (corner + 1, Move::Down),
+            (corner + 1 + self.size, Move::Left),
+            (corner + self.size, Move::Up),
+        ];
+        let blank_goal = self.index(goal.position(0));
+        self.move_blank_to(blank_goal, None)?;
+        let start = cycle
+            .iter()
+            .position(|&(cell, _)| cell == blank_goal)
+            .unwrap();
+        for _ in 0..3 {
+            let solved = cycle.iter().all(|&(cell, _)| {
+                let value = self.cells[cell];
+                self.index(goal.position(value)) == cell
+            });
+            if solved {
+                return Ok(());
+            }
+            for i in 0..4 {
+                self.apply(cycle[(start + i) % 4].1)?;
+            }
+        }
+        Err(anyhow!("No solution"))
+    }
+
+    /// Place the edge lines until the unsolved rectangle fits in a `core` x `core` square,
+    /// and return its left, top, right and bottom cells
+    pub(super) fn peel(
+        &mut self,
+        goal: &Goal,
+        core: usize,
+        deadline: Option<Instant>,
+    ) -> Result<(usize, usize, usize, usize)> {
+        let blank_goal = goal.position(0);
+        let (mut left, mut top) = (0, 0);
+        let (mut right, mut bottom) = (self.size - 1, self.size - 1);
+        loop {
+            if let Some(deadline) = deadline {
+                if Instant::now() > deadline {
+                    return Err(Timeout.into());
+                }
+            }
+            let (width, height) = (right - left + 1, bottom - top + 1);
+            if width <= core && height <= core {
+                return Ok((left, top, right, bottom));
+            }
+            if height > 2 && (height >= width || width <= 2) {
+                let y = if blank_goal.y != top { top } else { bottom };
+                let line: Vec<usize> = (left..=right).map(|x| self.index(Pos::new(x, y))).collect();
+                if y == top {
+                    self.solve_line(goal, &line, Move::Right, Move::Down)?;
+                    top += 1;
+                } else {
+                    self.solve_line(goal, &line, Move::Right, Move::Up)?;
+                    bottom -= 1;
+                }
+            } else {
+                let x = if blank_goal.x != left { left } else { right };
+                let line: Vec<usize> = (top..=bottom).map(|y| self.index(Pos::new(x, y))).collect();
+                if x == left {
+                    self.solve_line(goal, &line, Move::Down, Move::Right)?;
+                    left += 1;
+                } else {
+                    self.solve_line(goal, &line, Move::Down, Move::Left)?;
+                    right -= 1;
+                }
+            }
+        }
+    }
+
+    fn solve(&mut self, goal: &Goal, deadline: Option<Instant>) -> Result<()> {
+        let (left, top, _, _) = self.peel(goal, 2, deadline)?;
+        self.solve_block(goal, left, top)
+    }
+
+    /// Current board
+    pub(super) fn puzzle(&self) -> Result<Puzzle> {
+        let state = self
+            .cells
+            .chunks(self.size)
+            .map(|row| row.to_vec())
+            .collect();
+        Puzzle::new_from_state(state)
+    }
+
+    pub(super) fn into_path(self) -> Vec<Move> {
+        self.path
+    }
+}
+
+/// Solve any solvable board in polynomial time, with a valid but usually long path
+pub(super) fn solve(puzzle: &Puzzle, goal: &Goal, timeout: Option<u64>) -> Result<Output> {
+    goal.check_complete()?;
+    let start = Instant::now();
+    let deadline = timeout.map(|t| start + Duration::new(t, 0));
+    let mut builder = Builder::new(puzzle)?;
+    builder.solve(goal, deadline)?;
+    Ok(Output::new(
+        SearchStats::default(),
+        start.elapsed().as_secs_f64(),
+        builder.path,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(puzzle: &Puzzle, goal: &Goal) -> Result<usize> {
+        let output = solve(puzzle, goal, None)?;
+        let mut state = puzzle.clone();
+        for m in &output.path {
+            state.move_blank(*m)?;
+        }
+        assert!(goal.is_reached(&state));
+        Ok(output.path.len())
+    }
+
+    #[test]
+    fn test_constructive_small() -> Result<()> {
+        for size in 2..=5 {
+            for goal in [Goal::snail(size), Goal::row_major(size)] {
+                for _ in 0..20 {
+                    check(&Puzzle::generate_solvable_to(size, &goal)?, &goal)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_constructive_solved() -> Result<()> {
+        assert_eq!(check(&Puzzle::new_answer(4), &Goal::snail(4))?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_constructive_large() -> Result<()> {
+        let goal = Goal::snail(20);
+        check(&Puzzle::generate_solvable_to(20, &goal)?, &goal)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_constructive_unsolvable() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert!(solve(&puzzle, &Goal::snail(3), None).is_err());
+        Ok(())
+    }
+}
@@ -1,13 +1,23 @@
-use super::{astar, Heuristic, Output};
-use crate::Puzzle;
+use super::{astar, Heuristic, OpenOptions, Output, Session};
+use crate::{Goal, Puzzle};
 use anyhow::Result;
 
-pub(super) fn solve(puzzle: &Puzzle, heuristic: Heuristic, timeout: Option<u64>) -> Result<Output> {
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    options: OpenOptions,
+    session: Session,
+) -> Result<Output> {
     astar(
         puzzle.clone(),
+        goal,
         Heuristic::get_heuristic(&heuristic)?,
         true,
         timeout,
+        options,
+        session,
     )
 }
 
@@ -18,7 +28,14 @@ mod tests {
     #[test]
     fn test_greedy_trivial() -> Result<()> {
         let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![0, 8, 4], vec![7, 6, 5]])?;
-        let output = solve(&puzzle, Heuristic::Hamming, None)?;
+        let output = solve(
+            &puzzle,
+            &Goal::snail(3),
+            Heuristic::Hamming,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )?;
         assert!(output.path.len() == 1);
         assert!(output.path[0] == crate::Move::Right);
         Ok(())
@@ -27,7 +44,14 @@ mod tests {
     #[test]
     fn test_greedy() -> Result<()> {
         let mut puzzle = Puzzle::new_from_state(vec![vec![0, 2, 3], vec![1, 8, 4], vec![7, 6, 5]])?;
-        let output = solve(&puzzle, Heuristic::Hamming, None)?;
+        let output = solve(
+            &puzzle,
+            &Goal::snail(3),
+            Heuristic::Hamming,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )?;
         assert!(output.path.len() == 2);
         for m in output.path {
             puzzle.move_blank(m).unwrap();
@@ -40,6 +64,14 @@ mod tests {
     fn test_greedy_unsolvable() {
         let puzzle =
             Puzzle::new_from_state(vec![vec![1, 0, 6], vec![5, 3, 8], vec![4, 2, 7]]).unwrap();
-        assert!(solve(&puzzle, Heuristic::Hamming, None).is_err())
+        assert!(solve(
+            &puzzle,
+            &Goal::snail(3),
+            Heuristic::Hamming,
+            None,
+            OpenOptions::default(),
+            Session::default()
+        )
+        .is_err())
     }
 }
@@ -2,7 +2,8 @@ mod inversion_distance;
 
 pub use inversion_distance::inversion_distance;
 
-use crate::{Pos, Puzzle};
+use crate::oracle::{exact, Oracle};
+use crate::{Goal, Pos, Puzzle, Topology};
 use anyhow::{anyhow, Result};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -11,48 +12,132 @@ pub enum Heuristic {
     Hamming,
     LinearConflict,
     InversionDistance,
+    Exact,
     None,
 }
 
 impl Heuristic {
-    pub fn get_heuristic(&self) -> Result<fn(&Puzzle) -> usize> {
+    pub fn get_heuristic(&self) -> Result<fn(&Puzzle, &Goal) -> usize> {
         let func = match self {
             Heuristic::Manhattan => manhattan,
             Heuristic::Hamming => hamming,
             Heuristic::LinearConflict => linear_conflict,
             Heuristic::InversionDistance => inversion_distance,
+            Heuristic::Exact => exact,
             Heuristic::None => return Err(anyhow!("Heuristic not set")),
         };
         Ok(func)
     }
+
+    /// Make sure the heuristic can estimate the distance to the goal,
+    /// and give the goal the exact distances resolved once for the whole search
+    pub fn prepare_goal(&self, goal: &Goal) -> Result<Goal> {
+        match self {
+            Heuristic::Exact if goal.oracle().is_some() => {}
+            Heuristic::Exact => return Ok(goal.clone().with_oracle(Oracle::get(goal)?)),
+            Heuristic::InversionDistance if goal.is_partial() => {
+                return Err(anyhow!(
+                    "The inversion_distance heuristic needs a goal without wildcards."
+                ));
+            }
+            _ => {}
+        }
+        Ok(goal.clone())
+    }
+
+    /// Whether the heuristic never overestimates the distance on boards of the given topology,
+    /// so that the optimal algorithms find the shortest solutions with it
+    pub fn is_admissible(&self, topology: Topology) -> bool {
+        match topology {
+            Topology::Plane => matches!(self, Heuristic::InversionDistance | Heuristic::Exact),
+            Topology::Torus => matches!(self, Heuristic::Manhattan | Heuristic::Hamming),
+        }
+    }
+
+    /// Admissible heuristic towards the goal on boards of the given topology,
+    /// none on a plane when the goal has wildcards
+    pub fn admissible(goal: &Goal, topology: Topology) -> Heuristic {
+        match topology {
+            Topology::Torus => Heuristic::Manhattan,
+            Topology::Plane if goal.is_partial() => Heuristic::None,
+            Topology::Plane => Heuristic::InversionDistance,
+        }
+    }
+
+    /// Make sure the heuristic never overestimates the distance on boards of the given topology
+    pub fn check_topology(&self, topology: Topology) -> Result<()> {
+        match (self, topology) {
+            (
+                Heuristic::LinearConflict | Heuristic::InversionDistance | Heuristic::Exact,
+                Topology::Torus,
+            ) => Err(anyhow!(
+                "The {} heuristic cannot be used on a torus board. Use manhattan or hamming",
+                self
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Heuristic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Heuristic::Manhattan => "manhattan",
+            Heuristic::Hamming => "hamming",
+            Heuristic::LinearConflict => "linear_conflict",
+            Heuristic::InversionDistance => "inversion_distance",
+            Heuristic::Exact => "exact",
+            Heuristic::None => "none",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Distance between two coordinates, the shorter way round on a torus
+fn axis_distance(from: usize, to: usize, size: usize, topology: Topology) -> usize {
+    let distance = from.abs_diff(to);
+    match topology {
+        Topology::Plane => distance,
+        Topology::Torus => distance.min(size - distance),
+    }
+}
+
+/// Tiles the manhattan and hamming distances count, every one but the wildcards.
+/// The blank is left out on a torus, so that the wrapped distances stay admissible.
+fn is_estimated(value: usize, goal: &Goal, topology: Topology) -> bool {
+    goal.is_fixed(value) && !(value == 0 && topology == Topology::Torus)
 }
 
 // calculate manhattan distance
-pub fn manhattan(puzzle: &Puzzle) -> usize {
+pub fn manhattan(puzzle: &Puzzle, goal: &Goal) -> usize {
     let size = puzzle.get_size();
-    let answer_map = Puzzle::generate_answer_pos_map(size);
+    let topology = puzzle.get_topology();
     let mut distance = 0;
     for i in 0..size * size {
         let puzzle_pos = Pos::new(i % size, i / size);
         if let Ok(puzzle_value) = puzzle.get(puzzle_pos) {
-            let answer_pos = answer_map.get(&puzzle_value).unwrap();
-            distance += (puzzle_pos.x as isize - answer_pos.x as isize).unsigned_abs()
-                + (puzzle_pos.y as isize - answer_pos.y as isize).unsigned_abs();
+            if !is_estimated(puzzle_value, goal, topology) {
+                continue;
+            }
+            let answer_pos = goal.position(puzzle_value);
+            distance += axis_distance(puzzle_pos.x, answer_pos.x, size, topology)
+                + axis_distance(puzzle_pos.y, answer_pos.y, size, topology);
         }
     }
     distance
 }
 
 // calculate hamming distance
-pub fn hamming(puzzle: &Puzzle) -> usize {
+pub fn hamming(puzzle: &Puzzle, goal: &Goal) -> usize {
     let size = puzzle.get_size();
-    let answer = Puzzle::new_answer(size);
+    let topology = puzzle.get_topology();
     let mut distance = 0;
     for i in 0..size * size {
         let puzzle_pos = Pos::new(i % size, i / size);
         if let Ok(puzzle_value) = puzzle.get(puzzle_pos) {
-            let answer_value = answer.get(puzzle_pos).unwrap();
-            if puzzle_value != answer_value {
+            if is_estimated(puzzle_value, goal, topology)
+                && goal.position(puzzle_value) != puzzle_pos
+            {
                 distance += 1;
             }
         }
@@ -64,39 +149,40 @@ pub fn hamming(puzzle: &Puzzle) -> usize {
 // Two tiles t_j and t_k are in linear conflict if t_j and t_k are in the same line,
 // the goal position of t_j and t_k are both in that line, t_j is to the right of t_k,
 // and the goal position of t_j is to the left of the goal position of t_k.
-pub fn linear_conflict(puzzle: &Puzzle) -> usize {
+pub fn linear_conflict(puzzle: &Puzzle, goal: &Goal) -> usize {
     let size = puzzle.get_size();
-    let mut distance = manhattan(puzzle);
+    let mut distance = manhattan(puzzle, goal);
     let mut conflicts = 0;
 
     for i in 0..size {
-        conflicts += count_row_conflicts(puzzle, i);
-        conflicts += count_col_conflicts(puzzle, i);
+        conflicts += count_row_conflicts(puzzle, goal, i);
+        conflicts += count_col_conflicts(puzzle, goal, i);
     }
     distance += conflicts * 2;
 
     distance
 }
 
-fn count_row_conflicts(puzzle: &Puzzle, row: usize) -> usize {
+/// Tiles whose goal position matters, neither the blank nor a wildcard
+fn is_counted(value: usize, goal: &Goal) -> bool {
+    value != 0 && goal.is_fixed(value)
+}
+
+fn count_row_conflicts(puzzle: &Puzzle, goal: &Goal, row: usize) -> usize {
     let size = puzzle.get_size();
     let mut conflicts = 0;
-    let answer_map = Puzzle::generate_answer_pos_map(size);
 
     for i in 0..size {
-        let base_pos = Pos::new(i, row);
-        let base_value = puzzle.get(base_pos).unwrap();
-        if !puzzle.is_in_final_row(base_pos) {
+        let base_value = puzzle.get(Pos::new(i, row)).unwrap();
+        if !is_counted(base_value, goal) || goal.position(base_value).y != row {
             continue;
         }
         for j in i + 1..size {
-            let comparison_pos = Pos::new(j, row);
-            let comparison_value = puzzle.get(comparison_pos).unwrap();
-            if !puzzle.is_in_final_row(comparison_pos) {
+            let comparison_value = puzzle.get(Pos::new(j, row)).unwrap();
+            if !is_counted(comparison_value, goal) || goal.position(comparison_value).y != row {
                 continue;
             }
-            if answer_map.get(&base_value).unwrap().x > answer_map.get(&comparison_value).unwrap().x
-            {
+            if goal.position(base_value).x > goal.position(comparison_value).x {
                 conflicts += 1;
             }
         }
@@ -104,28 +190,103 @@ fn count_row_conflicts(puzzle: &Puzzle, row: usize) -> usize {
     conflicts
 }
 
-fn count_col_conflicts(puzzle: &Puzzle, col: usize) -> usize {
+fn count_col_conflicts(puzzle: &Puzzle, goal: &Goal, col: usize) -> usize {
     let size = puzzle.get_size();
     let mut conflicts = 0;
-    let answer_map = Puzzle::generate_answer_pos_map(size);
 
     for i in 0..size {
-        let base_pos = Pos::new(col, i);
-        let base_value = puzzle.get(base_pos).unwrap();
-        if !puzzle.is_in_final_col(base_pos) {
+        let base_value = puzzle.get(Pos::new(col, i)).unwrap();
+        if !is_counted(base_value, goal) || goal.position(base_value).x != col {
             continue;
         }
         for j in i + 1..size {
-            let comparison_pos = Pos::new(col, j);
-            let comparison_value = puzzle.get(comparison_pos).unwrap();
-            if !puzzle.is_in_final_col(comparison_pos) {
+            let comparison_value = puzzle.get(Pos::new(col, j)).unwrap();
+            if !is_counted(comparison_value, goal) || goal.position(comparison_value).x != col {
                 continue;
             }
-            if answer_map.get(&base_value).unwrap().y > answer_map.get(&comparison_value).unwrap().y
-            {
+            if goal.position(base_value).y > goal.position(comparison_value).y {
                 conflicts += 1;
             }
         }
     }
     conflicts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manhattan() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 4, 0], vec![7, 6, 5]])?;
+        assert_eq!(manhattan(&puzzle, &goal), 2);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert_eq!(manhattan(&puzzle, &goal), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_manhattan_torus() -> Result<()> {
+        let goal = Goal::row_major(4);
+        let mut puzzle = Puzzle::new_from_state(vec![
+            vec![3, 1, 2, 0],
+            vec![4, 5, 6, 7],
+            vec![8, 9, 10, 11],
+            vec![12, 13, 14, 15],
+        ])?;
+        assert_eq!(manhattan(&puzzle, &goal), 6);
+        puzzle.set_topology(Topology::Torus);
+        // The 3 wraps round to its goal in one move, and the blank is not counted
+        assert_eq!(manhattan(&puzzle, &goal), 1);
+        assert!(Heuristic::Manhattan.check_topology(Topology::Torus).is_ok());
+        assert!(Heuristic::LinearConflict
+            .check_topology(Topology::Torus)
+            .is_err());
+        assert!(Heuristic::Exact.check_topology(Topology::Plane).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hamming() -> Result<()> {
+        let goal = Goal::row_major(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 0, 2], vec![3, 4, 5], vec![6, 7, 8]])?;
+        assert_eq!(hamming(&puzzle, &goal), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial_goal() -> Result<()> {
+        let goal = Goal::parse_text("../puzzles/first_row_target.txt")?;
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert_eq!(manhattan(&puzzle, &goal), 2);
+        assert_eq!(hamming(&puzzle, &goal), 2);
+        assert_eq!(linear_conflict(&puzzle, &goal), 4);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![6, 5, 4], vec![0, 8, 7]])?;
+        assert_eq!(linear_conflict(&puzzle, &goal), 0);
+        assert!(Heuristic::InversionDistance.prepare_goal(&goal).is_err());
+        assert!(Heuristic::Exact.prepare_goal(&goal).is_err());
+        assert!(Heuristic::Manhattan.prepare_goal(&goal).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 6, 4], vec![7, 0, 5]])?;
+        let prepared = Heuristic::Exact.prepare_goal(&goal)?;
+        assert_eq!(exact(&puzzle, &prepared), 1);
+        // Without the oracle nothing is known
+        assert_eq!(exact(&puzzle, &goal), 0);
+        assert!(Heuristic::Exact.prepare_goal(&Goal::snail(4)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_conflict() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert_eq!(linear_conflict(&puzzle, &goal), 4);
+        Ok(())
+    }
+}
@@ -1,32 +1,30 @@
-use crate::{Pos, Puzzle};
+use crate::{Goal, Pos, Puzzle};
 
 /// Inversion distance
 ///
 /// # Reference
 ///
 /// https://michael.kim/blog/puzzle
-pub fn inversion_distance(puzzle: &Puzzle) -> usize {
+pub fn inversion_distance(puzzle: &Puzzle, goal: &Goal) -> usize {
     let size = puzzle.get_size();
-    let flat_state = puzzle_to_vec_lr_tb(puzzle);
+    let flat_state = puzzle_to_vec_lr_tb(puzzle, goal);
     let inversions = count_inversions(&flat_state);
     let vertical = calculate_move_lower_limit(inversions, size);
-    let flat_state = puzzle_to_vec_tb_lr(puzzle);
+    let flat_state = puzzle_to_vec_tb_lr(puzzle, goal);
     let inversions = count_inversions(&flat_state);
     let horizontal = calculate_move_lower_limit(inversions, size);
     vertical + horizontal
 }
 
 /// Convert puzzle to a vector from left to right and top to bottom
-fn puzzle_to_vec_lr_tb(puzzle: &Puzzle) -> Vec<usize> {
+fn puzzle_to_vec_lr_tb(puzzle: &Puzzle, goal: &Goal) -> Vec<usize> {
     let size = puzzle.get_size();
-    let answer_map = Puzzle::generate_arrange_order_answer_map(size, false);
     let mut flat_state = Vec::with_capacity(size * size - 1);
     for y in 0..size {
         for x in 0..size {
             if let Ok(value) = puzzle.get(Pos::new(x, y)) {
                 if value != 0 {
-                    let answer_pos = answer_map.get(&value).unwrap();
-                    flat_state.push(*answer_pos);
+                    flat_state.push(goal.index(value) + 1);
                 }
             }
         }
@@ -35,9 +33,8 @@ fn puzzle_to_vec_lr_tb(puzzle: &Puzzle) -> Vec<usize> {
 }
 
 /// Convert puzzle to a vector from top to bottom and left to right
-fn puzzle_to_vec_tb_lr(puzzle: &Puzzle) -> Vec<usize> {
+fn puzzle_to_vec_tb_lr(puzzle: &Puzzle, goal: &Goal) -> Vec<usize> {
     let size = puzzle.get_size();
-    let answer_map = Puzzle::generate_arrange_order_answer_map(size, false);
     let mut value_map = vec![0; size * size + 1];
     let mut count = 1;
     for x in 0..size {
@@ -53,8 +50,7 @@ fn puzzle_to_vec_tb_lr(puzzle: &Puzzle) -> Vec<usize> {
         for y in 0..size {
             if let Ok(value) = puzzle.get(Pos::new(x, y)) {
                 if value != 0 {
-                    let answer_pos = answer_map.get(&value).unwrap();
-                    flat_state.push(value_map[*answer_pos]);
+                    flat_state.push(value_map[goal.index(value) + 1]);
                 }
             }
         }
@@ -96,7 +92,7 @@ mod tests {
     #[test]
     fn test_puzzle_to_vec_lr_tb_trivial() -> Result<()> {
         let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
-        let flat_state = puzzle_to_vec_lr_tb(&puzzle);
+        let flat_state = puzzle_to_vec_lr_tb(&puzzle, &Goal::snail(3));
         assert_eq!(flat_state, vec![1, 2, 3, 4, 6, 7, 8, 9]);
         Ok(())
     }
@@ -104,7 +100,7 @@ mod tests {
     #[test]
     fn test_puzzle_to_vec_lr_tb_normal() -> Result<()> {
         let puzzle = Puzzle::new_from_state(vec![vec![1, 0, 3], vec![2, 8, 4], vec![7, 5, 6]])?;
-        let flat_state = puzzle_to_vec_lr_tb(&puzzle);
+        let flat_state = puzzle_to_vec_lr_tb(&puzzle, &Goal::snail(3));
         assert_eq!(flat_state, vec![1, 3, 2, 4, 6, 7, 9, 8]);
         Ok(())
     }
@@ -112,7 +108,7 @@ mod tests {
     #[test]
     fn test_puzzle_to_vec_tb_lr_trivial() -> Result<()> {
         let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
-        let flat_state = puzzle_to_vec_tb_lr(&puzzle);
+        let flat_state = puzzle_to_vec_tb_lr(&puzzle, &Goal::snail(3));
         assert_eq!(flat_state, vec![1, 2, 3, 4, 6, 7, 8, 9]);
         Ok(())
     }
@@ -120,7 +116,7 @@ mod tests {
     #[test]
     fn test_puzzle_to_vec_tb_lr_normal() -> Result<()> {
         let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 4, 0], vec![7, 5, 6]])?;
-        let flat_state = puzzle_to_vec_tb_lr(&puzzle);
+        let flat_state = puzzle_to_vec_tb_lr(&puzzle, &Goal::snail(3));
         assert_eq!(flat_state, vec![4, 2, 3, 1, 8, 9, 7, 6]);
         Ok(())
     }
@@ -175,7 +171,7 @@ mod tests {
     fn test_inversion_distance_trivial() {
         let puzzle =
             Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 0, 4], vec![7, 6, 5]]).unwrap();
-        let distance = inversion_distance(&puzzle);
+        let distance = inversion_distance(&puzzle, &Goal::snail(3));
         assert_eq!(distance, 0);
     }
 
@@ -183,7 +179,7 @@ mod tests {
     fn test_inversion_distance_normal() {
         let puzzle =
             Puzzle::new_from_state(vec![vec![2, 8, 3], vec![1, 0, 4], vec![7, 6, 5]]).unwrap();
-        let distance = inversion_distance(&puzzle);
+        let distance = inversion_distance(&puzzle, &Goal::snail(3));
         assert_eq!(distance, 4);
     }
 }
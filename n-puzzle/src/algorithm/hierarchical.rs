@@ -0,0 +1,170 @@
+use super::constructive::Builder;
+use super::ida_star::{self, IdaOptions};
+use super::{Heuristic, Output, Phase, Session};
+use crate::{Goal, Pos, Puzzle};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Side of the block left to the search by default
+pub const DEFAULT_CORE_SIZE: usize = 3;
+
+/// The block of `core` x `core` cells at `left`, `top` as a puzzle of its own,
+/// with the tiles renumbered in the reading order of their goal cells
+fn extract_core(
+    board: &Puzzle,
+    goal: &Goal,
+    left: usize,
+    top: usize,
+    core: usize,
+) -> Result<(Puzzle, Goal)> {
+    let cells: Vec<Pos> = (0..core * core)
+        .map(|i| Pos::new(left + i % core, top + i / core))
+        .collect();
+    let mut labels = HashMap::from([(0, 0)]);
+    for &pos in &cells {
+        let value = goal.target().get(pos)?;
+        if value != 0 {
+            let label = labels.len();
+            labels.insert(value, label);
+        }
+    }
+    let relabel = |puzzle: &Puzzle| -> Result<Vec<Vec<usize>>> {
+        let mut state = vec![vec![0; core]; core];
+        for (i, &pos) in cells.iter().enumerate() {
+            let value = puzzle.get(pos)?;
+            state[i / core][i % core] = *labels
+                .get(&value)
+                .ok_or_else(|| anyhow!("Tile {} does not belong to the core", value))?;
+        }
+        Ok(state)
+    };
+    let start = Puzzle::new_from_state(relabel(board)?)?;
+    let target = Puzzle::new_from_state(relabel(goal.target())?)?;
+    Ok((start, Goal::from_puzzle(target)))
+}
+
+/// Place the outer lines with the constructive solver, then search the last `core` x `core` block
+/// with IDA*, which is optimal for the block with an admissible heuristic. The moves of the block are the same on the whole board.
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    core: usize,
+    timeout: Option<u64>,
+    options: IdaOptions,
+) -> Result<Output> {
+    goal.check_complete()?;
+    let start = Instant::now();
+    let deadline = timeout.map(|t| start + Duration::new(t, 0));
+    let mut builder = Builder::new(puzzle)?;
+    let (left, top, right, bottom) = builder.peel(goal, core, deadline)?;
+    if right - left != bottom - top {
+        return Err(anyhow!("The unsolved block is not a square"));
+    }
+    let board = builder.puzzle()?;
+    let (core_start, core_goal) = extract_core(&board, goal, left, top, right - left + 1)?;
+    let core_goal = heuristic.prepare_goal(&core_goal)?;
+    let remaining = timeout.map(|t| t.saturating_sub(start.elapsed().as_secs()));
+    let inner = ida_star::solve_with(
+        &core_start,
+        &core_goal,
+        heuristic,
+        remaining,
+        options,
+        Session::default(),
+    )?;
+    let mut path = builder.into_path();
+    let phases = vec![
+        Phase::new("outer rings", path.len()),
+        Phase::new("core", inner.path.len()),
+    ];
+    path.extend(inner.path);
+    let mut output = Output::new(inner.stats, start.elapsed().as_secs_f64(), path);
+    output.phases = phases;
+    output.transpositions = inner.transpositions;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    fn check(puzzle: &Puzzle, goal: &Goal, core: usize) -> Result<Output> {
+        let output = solve(
+            puzzle,
+            goal,
+            Heuristic::Manhattan,
+            core,
+            None,
+            IdaOptions::default(),
+        )?;
+        let mut state = puzzle.clone();
+        for m in &output.path {
+            state.move_blank(*m)?;
+        }
+        assert!(goal.is_reached(&state));
+        let phases: usize = output.phases.iter().map(|phase| phase.moves).sum();
+        assert_eq!(phases, output.path.len());
+        Ok(output)
+    }
+
+    #[test]
+    fn test_hierarchical() -> Result<()> {
+        for size in 2..=7 {
+            for goal in [Goal::snail(size), Goal::row_major(size)] {
+                for _ in 0..3 {
+                    check(&Puzzle::generate_solvable_to(size, &goal)?, &goal, 3)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hierarchical_core_is_optimal() -> Result<()> {
+        // The whole 3x3 board is the core
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 8, 3], vec![1, 6, 4], vec![7, 0, 5]])?;
+        let output = check(&puzzle, &goal, 3)?;
+        assert_eq!(output.path.len(), 5);
+        assert_eq!(output.phases[0].moves, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_core() -> Result<()> {
+        let goal = Goal::snail(4);
+        let (start, core_goal) = extract_core(goal.target(), &goal, 1, 1, 2)?;
+        // The inner 2x2 block of the snail goal is 13 14 / 0 15
+        assert_eq!(start, Puzzle::new_from_state(vec![vec![1, 2], vec![0, 3]])?);
+        assert!(core_goal.is_reached(&start));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hierarchical_shorter_than_constructive() -> Result<()> {
+        let goal = Goal::snail(6);
+        // A few moves from the goal, so that the 4x4 core is searched quickly
+        let mut puzzle = goal.target().clone();
+        let moves = "LLURULDRRDRU".chars().map(|c| match c {
+            'U' => Move::Up,
+            'D' => Move::Down,
+            'L' => Move::Left,
+            _ => Move::Right,
+        });
+        for m in moves {
+            puzzle.move_blank(m)?;
+        }
+        let output = check(&puzzle, &goal, 4)?;
+        let constructive = super::super::constructive::solve(&puzzle, &goal, None)?;
+        // Both place the same outer lines with the same moves
+        assert_eq!(
+            constructive.path[..output.phases[0].moves],
+            output.path[..output.phases[0].moves]
+        );
+        assert!(output.phases[1].moves <= constructive.path.len() - output.phases[0].moves);
+        Ok(())
+    }
+}
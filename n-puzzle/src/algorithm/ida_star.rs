@@ -0,0 +1,564 @@
+use super::checkpoint::{Checkpointer, Resume, SearchState};
+use super::transposition::{Replacement, TranspositionTable};
+use super::{Heuristic, Output, SearchStats, Session, Timeout};
+use crate::{Goal, Move, Puzzle};
+use anyhow::{anyhow, Result};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Root subtrees handed out per worker thread, so that the workers stay busy until the end
+const SUBTREES_PER_THREAD: usize = 16;
+/// Most worker threads, far more than the cores of a usual machine
+pub const MAX_THREADS: usize = 64;
+
+/// Options of the IDA* search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct IdaOptions {
+    pub(super) threads: usize,
+    /// Capacity and replacement policy of the transposition table, if any
+    pub(super) table: Option<(usize, Replacement)>,
+}
+
+impl Default for IdaOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            table: None,
+        }
+    }
+}
+
+/// Result of one depth-first iteration
+enum Search {
+    Found,
+    /// Lowest f cost beyond the bound, if any
+    Exceeded(Option<usize>),
+}
+
+struct IdaStar<'a> {
+    goal: &'a Goal,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
+    path: Vec<Move>,
+    stats: SearchStats,
+    start: Instant,
+    timeout: Option<Duration>,
+    /// Set by another worker once a solution is found
+    stop: Option<&'a AtomicBool>,
+    /// Lower bounds proven by the previous iterations
+    table: Option<TranspositionTable>,
+    /// Lowest cost beyond the bound found so far in the iteration
+    next_bound: Option<usize>,
+    /// Path of the checkpoint the iteration resumes from, until it is reached
+    resume: Option<Vec<Move>>,
+    checkpointer: Option<Checkpointer>,
+    /// Seconds spent before the search was resumed
+    elapsed: f64,
+}
+
+impl IdaStar<'_> {
+    /// Move to search first below the current state, skipping the subtrees searched before the checkpoint
+    fn resume_move(&mut self) -> Option<Move> {
+        let depth = self.path.len();
+        match &self.resume {
+            Some(path) if depth < path.len() => Some(path[depth]),
+            _ => {
+                self.resume = None;
+                None
+            }
+        }
+    }
+
+    /// Search below `puzzle`, which is moved in place and restored before returning, unless found.
+    /// `through_parent` is a lower bound of the distance to the goal through the previous state.
+    fn search(
+        &mut self,
+        puzzle: &mut Puzzle,
+        moved: usize,
+        bound: usize,
+        through_parent: usize,
+    ) -> Result<Search> {
+        let mut estimate = (self.heuristic)(puzzle, self.goal);
+        self.stats.heuristic_evaluations += 1;
+        if let Some(proven) = self.table.as_mut().and_then(|table| table.get(puzzle)) {
+            estimate = estimate.max(proven);
+        }
+        let cost = moved + estimate;
+        if cost > bound {
+            self.next_bound = Some(self.next_bound.map_or(cost, |next| next.min(cost)));
+            return Ok(Search::Exceeded(Some(cost)));
+        }
+        if self.goal.is_reached(puzzle) {
+            return Ok(Search::Found);
+        }
+        if let Some(duration) = self.timeout {
+            if self.start.elapsed() > duration {
+                return Err(Timeout.into());
+            }
+        }
+        if self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+            return Ok(Search::Exceeded(None));
+        }
+        if let Some(checkpointer) = &mut self.checkpointer {
+            if self.resume.is_none() && checkpointer.is_due() {
+                let elapsed = self.elapsed + self.start.elapsed().as_secs_f64();
+                checkpointer.save_ida_star(
+                    &self.stats,
+                    elapsed,
+                    bound,
+                    self.next_bound,
+                    &self.path,
+                )?;
+            }
+        }
+        let mut resume_move = self.resume_move();
+        let resumed = resume_move.is_some();
+        self.stats.nodes_expanded += 1;
+        let mut next_bound: Option<usize> = None;
+        for m in Move::list() {
+            if resume_move.is_some_and(|resume| resume != m) {
+                continue;
+            }
+            // Moving straight back never shortens a path
+            if self.path.last() == Some(&m.opposite()) {
+                continue;
+            }
+            if puzzle.move_blank(m).is_err() {
+                continue;
+            }
+            self.stats.nodes_generated += 1;
+            self.path.push(m);
+            self.stats.max_memory = self.stats.max_memory.max(self.path.len());
+            let search = self.search(puzzle, moved + 1, bound, estimate + 1)?;
+            if resume_move.take().is_some() {
+                self.resume = None;
+            }
+            match search {
+                Search::Found => return Ok(Search::Found),
+                Search::Exceeded(Some(cost)) => {
+                    next_bound = Some(next_bound.map_or(cost, |bound| bound.min(cost)))
+                }
+                Search::Exceeded(None) => {}
+            }
+            self.path.pop();
+            puzzle.move_blank(m.opposite())?;
+        }
+        // The subtrees skipped when resuming are missing from the costs beyond the bound
+        if let Some(table) = self.table.as_mut().filter(|_| !resumed) {
+            // The move back to the previous state was not searched, so its route still bounds the distance
+            let proven =
+                next_bound.map_or(through_parent, |cost| (cost - moved).min(through_parent));
+            if proven > estimate {
+                table.store(puzzle, proven, bound - moved);
+            }
+        }
+        Ok(Search::Exceeded(next_bound))
+    }
+}
+
+/// Iterative deepening A*: memory stays linear in the solution length,
+/// at the price of expanding the states near the start again at every iteration.
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    table: Option<(usize, Replacement)>,
+    mut session: Session,
+) -> Result<Output> {
+    let elapsed = session.elapsed();
+    let mut search = IdaStar {
+        goal,
+        heuristic: heuristic.get_heuristic()?,
+        path: Vec::new(),
+        stats: SearchStats {
+            nodes_generated: 1,
+            ..Default::default()
+        },
+        start: Instant::now(),
+        timeout: timeout.map(|t| Duration::new(t, 0)),
+        stop: None,
+        table: table.map(|(capacity, policy)| TranspositionTable::new(capacity, policy)),
+        next_bound: None,
+        resume: None,
+        checkpointer: session.checkpointer.take(),
+        elapsed,
+    };
+    let mut bound = match session.resume.take() {
+        None => (search.heuristic)(puzzle, goal),
+        Some(Resume {
+            stats,
+            search:
+                SearchState::IdaStar {
+                    bound,
+                    next_bound,
+                    path,
+                },
+            ..
+        }) => {
+            search.stats = stats;
+            search.next_bound = next_bound;
+            search.resume = Some(path);
+            bound
+        }
+        Some(_) => {
+            return Err(anyhow!(
+                "The checkpoint was not written by an ida_star search."
+            ))
+        }
+    };
+    loop {
+        if let Search::Found = search.search(&mut puzzle.clone(), 0, bound, usize::MAX)? {
+            break;
+        }
+        bound = search
+            .next_bound
+            .take()
+            .ok_or_else(|| anyhow!("No solution"))?;
+    }
+    let mut output = Output::new(
+        search.stats,
+        elapsed + search.start.elapsed().as_secs_f64(),
+        search.path,
+    );
+    output.transpositions = search.table.map(|table| table.stats());
+    Ok(output)
+}
+
+/// States at the same depth below the start, none of them moving straight back
+enum Frontier {
+    /// The goal was reached while building the frontier, by this shortest path
+    Solved(Vec<Move>),
+    Subtrees(Vec<(Puzzle, Vec<Move>)>),
+}
+
+/// Expand the start breadth first until at least `count` states are reached
+fn split(puzzle: &Puzzle, goal: &Goal, count: usize, stats: &mut SearchStats) -> Result<Frontier> {
+    if goal.is_reached(puzzle) {
+        return Ok(Frontier::Solved(Vec::new()));
+    }
+    let mut level = vec![(puzzle.clone(), Vec::new())];
+    while level.len() < count {
+        let mut next_level = Vec::new();
+        for (state, path) in &level {
+            stats.nodes_expanded += 1;
+            for m in Move::list() {
+                if path.last() == Some(&m.opposite()) {
+                    continue;
+                }
+                let mut next = state.clone();
+                if next.move_blank(m).is_err() {
+                    continue;
+                }
+                let mut next_path: Vec<Move> = path.clone();
+                next_path.push(m);
+                stats.nodes_generated += 1;
+                if goal.is_reached(&next) {
+                    return Ok(Frontier::Solved(next_path));
+                }
+                next_level.push((next, next_path));
+            }
+        }
+        level = next_level;
+    }
+    stats.max_memory = level.len();
+    Ok(Frontier::Subtrees(level))
+}
+
+fn merge(total: &mut SearchStats, worker: &SearchStats) {
+    total.nodes_generated += worker.nodes_generated;
+    total.nodes_expanded += worker.nodes_expanded;
+    total.heuristic_evaluations += worker.heuristic_evaluations;
+    total.max_memory = total.max_memory.max(worker.max_memory);
+}
+
+/// What a worker thread found in one iteration
+struct Iteration {
+    stats: SearchStats,
+    path: Option<Vec<Move>>,
+    next_bound: Option<usize>,
+}
+
+/// IDA* with the subtrees a few moves below the start shared between `options.threads` workers.
+///
+/// Every iteration searches all subtrees with the same bound, and the bound only grows
+/// past the lowest cost exceeded by any worker, so the solution stays optimal.
+pub(super) fn solve_with(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    options: IdaOptions,
+    session: Session,
+) -> Result<Output> {
+    let threads = options.threads;
+    if threads <= 1 {
+        return solve(puzzle, goal, heuristic, timeout, options.table, session);
+    }
+    if session.checkpointer.is_some() || session.resume.is_some() {
+        return Err(anyhow!(
+            "Checkpoints cannot be used with more than one thread."
+        ));
+    }
+    if options.table.is_some() {
+        return Err(anyhow!(
+            "The transposition table cannot be shared between threads."
+        ));
+    }
+    let start = Instant::now();
+    let timeout = timeout.map(|t| Duration::new(t, 0));
+    let heuristic = heuristic.get_heuristic()?;
+    let mut stats = SearchStats {
+        nodes_generated: 1,
+        heuristic_evaluations: 1,
+        ..Default::default()
+    };
+    let subtrees = match split(puzzle, goal, threads * SUBTREES_PER_THREAD, &mut stats)? {
+        Frontier::Solved(path) => {
+            return Ok(Output::new(stats, start.elapsed().as_secs_f64(), path))
+        }
+        Frontier::Subtrees(subtrees) => subtrees,
+    };
+    let mut bound = heuristic(puzzle, goal);
+    loop {
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let iterations: Vec<Result<Iteration>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    let (next, stop, subtrees) = (&next, &stop, &subtrees);
+                    scope.spawn(move || -> Result<Iteration> {
+                        let mut worker = IdaStar {
+                            goal,
+                            heuristic,
+                            path: Vec::new(),
+                            stats: SearchStats::default(),
+                            start,
+                            timeout,
+                            stop: Some(stop),
+                            table: None,
+                            next_bound: None,
+                            resume: None,
+                            checkpointer: None,
+                            elapsed: 0.0,
+                        };
+                        let mut next_bound: Option<usize> = None;
+                        loop {
+                            let index = next.fetch_add(1, Ordering::SeqCst);
+                            if index >= subtrees.len() || stop.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            let (state, path) = &subtrees[index];
+                            worker.path = path.clone();
+                            let search =
+                                worker.search(&mut state.clone(), path.len(), bound, usize::MAX);
+                            match search {
+                                Ok(Search::Found) => {
+                                    stop.store(true, Ordering::Relaxed);
+                                    return Ok(Iteration {
+                                        stats: worker.stats,
+                                        path: Some(worker.path),
+                                        next_bound: None,
+                                    });
+                                }
+                                Ok(Search::Exceeded(Some(cost))) => {
+                                    next_bound =
+                                        Some(next_bound.map_or(cost, |bound| bound.min(cost)))
+                                }
+                                Ok(Search::Exceeded(None)) => {}
+                                Err(e) => {
+                                    stop.store(true, Ordering::Relaxed);
+                                    return Err(e);
+                                }
+                            }
+                        }
+                        Ok(Iteration {
+                            stats: worker.stats,
+                            path: None,
+                            next_bound,
+                        })
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .map_err(|_| anyhow!("A search thread panicked"))
+                        .and_then(|iteration| iteration)
+                })
+                .collect()
+        });
+        let mut found = None;
+        let mut next_bound: Option<usize> = None;
+        let mut error = None;
+        for iteration in iterations {
+            match iteration {
+                Ok(iteration) => {
+                    merge(&mut stats, &iteration.stats);
+                    found = found.or(iteration.path);
+                    if let Some(cost) = iteration.next_bound {
+                        next_bound = Some(next_bound.map_or(cost, |bound| bound.min(cost)));
+                    }
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        if let Some(path) = found {
+            return Ok(Output::new(stats, start.elapsed().as_secs_f64(), path));
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+        bound = next_bound.ok_or_else(|| anyhow!("No solution"))?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{astar, OpenOptions, Session};
+
+    fn threads(threads: usize) -> IdaOptions {
+        IdaOptions {
+            threads,
+            table: None,
+        }
+    }
+
+    #[test]
+    fn test_ida_star_optimal() -> Result<()> {
+        let goal = Goal::snail(3);
+        for _ in 0..5 {
+            let puzzle = Puzzle::generate_solvable_to(3, &goal)?;
+            let output = solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                None,
+                Session::default(),
+            )?;
+            let expected = astar::solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                OpenOptions::default(),
+                Session::default(),
+            )?;
+            assert_eq!(output.path.len(), expected.path.len());
+            let mut state = puzzle.clone();
+            for m in &output.path {
+                state.move_blank(*m)?;
+            }
+            assert!(goal.is_reached(&state));
+            assert!(output.stats.max_memory <= output.path.len().max(1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ida_star_solved() -> Result<()> {
+        let output = solve(
+            &Puzzle::new_answer(3),
+            &Goal::snail(3),
+            Heuristic::Manhattan,
+            None,
+            None,
+            Session::default(),
+        )?;
+        assert!(output.path.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ida_star_parallel() -> Result<()> {
+        let goal = Goal::snail(3);
+        for _ in 0..5 {
+            let puzzle = Puzzle::generate_solvable_to(3, &goal)?;
+            let output = solve_with(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                threads(4),
+                Session::default(),
+            )?;
+            let expected = solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                None,
+                Session::default(),
+            )?;
+            assert_eq!(output.path.len(), expected.path.len());
+            let mut state = puzzle.clone();
+            for m in &output.path {
+                state.move_blank(*m)?;
+            }
+            assert!(goal.is_reached(&state));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ida_star_parallel_short() -> Result<()> {
+        // Solutions shorter than the depth of the subtrees are found while splitting
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 6, 4], vec![7, 0, 5]])?;
+        let output = solve_with(
+            &puzzle,
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            threads(8),
+            Session::default(),
+        )?;
+        assert_eq!(output.path, vec![Move::Up]);
+        let output = solve_with(
+            goal.target(),
+            &goal,
+            Heuristic::Manhattan,
+            None,
+            threads(8),
+            Session::default(),
+        )?;
+        assert!(output.path.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ida_star_transposition_table() -> Result<()> {
+        let goal = Goal::snail(3);
+        for policy in [Replacement::Always, Replacement::Depth] {
+            for _ in 0..5 {
+                let puzzle = Puzzle::generate_solvable_to(3, &goal)?;
+                let plain = solve(
+                    &puzzle,
+                    &goal,
+                    Heuristic::Manhattan,
+                    None,
+                    None,
+                    Session::default(),
+                )?;
+                let output = solve(
+                    &puzzle,
+                    &goal,
+                    Heuristic::Manhattan,
+                    None,
+                    Some((1 << 12, policy)),
+                    Session::default(),
+                )?;
+                assert_eq!(output.path.len(), plain.path.len());
+                assert!(output.stats.nodes_expanded <= plain.stats.nodes_expanded);
+                let table = output.transpositions.unwrap();
+                assert_eq!(table.policy, policy);
+                assert!(table.stores >= table.evictions);
+            }
+        }
+        Ok(())
+    }
+}
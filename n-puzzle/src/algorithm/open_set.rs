@@ -1,12 +1,72 @@
-use crate::{Move, Puzzle};
+use crate::{Goal, Move, Puzzle};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::BinaryHeap;
 
+/// Which of the nodes of equal total cost is expanded first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TieBreak {
+    /// The node with the most moves, closest to the goal
+    HigherG,
+    /// The node with the lowest heuristic, which is the most moves for an equal total cost
+    LowerH,
+    /// The latest node
+    Lifo,
+    /// The earliest node
+    Fifo,
+    /// A random node, drawn from the given seed
+    Random(u64),
+}
+
+impl std::fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TieBreak::HigherG => write!(f, "higher_g"),
+            TieBreak::LowerH => write!(f, "lower_h"),
+            TieBreak::Lifo => write!(f, "lifo"),
+            TieBreak::Fifo => write!(f, "fifo"),
+            TieBreak::Random(seed) => write!(f, "random (seed {})", seed),
+        }
+    }
+}
+
+/// How the open set keeps its nodes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OpenList {
+    /// Binary heap, with O(log n) insertions and removals
+    #[default]
+    Heap,
+    /// Buckets indexed by total cost and moves, with O(1) insertions and removals.
+    /// Among the nodes of equal total cost, the one with the most moves is expanded first, then the latest one.
+    Buckets,
+}
+
+impl std::fmt::Display for OpenList {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            OpenList::Heap => "heap",
+            OpenList::Buckets => "buckets",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Options of the open set of the astar, uniform_cost and greedy searches
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OpenOptions {
+    pub list: OpenList,
+    /// Order of the nodes of equal total cost in a heap, in no particular order without one
+    pub tie_break: Option<TieBreak>,
+}
+
 #[derive(Clone, Debug)]
 pub struct OpenSetNode {
     state: Puzzle,
     path: Vec<Move>,
     moved_cost: usize,
     heuristics_cost: usize,
+    /// Order among the nodes of equal total cost, the highest first
+    tie: u64,
 }
 
 impl OpenSetNode {
@@ -14,14 +74,16 @@ impl OpenSetNode {
         state: Puzzle,
         path: Vec<Move>,
         moved_cost: usize,
-        heuristic: fn(&Puzzle) -> usize,
+        heuristic: fn(&Puzzle, &Goal) -> usize,
+        goal: &Goal,
     ) -> Self {
-        let heuristics_cost = heuristic(&state);
+        let heuristics_cost = heuristic(&state, goal);
         Self {
             state,
             path,
             moved_cost,
             heuristics_cost,
+            tie: 0,
         }
     }
 
@@ -37,8 +99,8 @@ impl OpenSetNode {
         self.moved_cost + self.heuristics_cost
     }
 
-    pub fn is_goal(&self) -> bool {
-        self.state.is_final_state()
+    pub fn is_goal(&self, goal: &Goal) -> bool {
+        goal.is_reached(&self.state)
     }
 
     pub fn state(&self) -> &Puzzle {
@@ -52,11 +114,21 @@ impl OpenSetNode {
     pub fn path(&self) -> &Vec<Move> {
         &self.path
     }
+
+    pub fn tie(&self) -> u64 {
+        self.tie
+    }
+
+    /// The same node with the order it had in another open set
+    pub fn with_tie(mut self, tie: u64) -> Self {
+        self.tie = tie;
+        self
+    }
 }
 
 impl PartialEq for OpenSetNode {
     fn eq(&self, other: &Self) -> bool {
-        self.total_cost() == other.total_cost()
+        self.total_cost() == other.total_cost() && self.tie == other.tie
     }
 }
 
@@ -70,38 +142,241 @@ impl PartialOrd for OpenSetNode {
 
 impl Ord for OpenSetNode {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (other.total_cost()).cmp(&self.total_cost())
+        (other.total_cost())
+            .cmp(&self.total_cost())
+            .then(self.tie.cmp(&other.tie))
+    }
+}
+
+/// Nodes by total cost, then by moves, for costs that are small integers
+#[derive(Debug, Default)]
+struct Buckets {
+    nodes: Vec<Vec<Vec<OpenSetNode>>>,
+    /// Number of nodes of each total cost
+    counts: Vec<usize>,
+    /// No node has a lower total cost
+    lowest: usize,
+    len: usize,
+}
+
+impl Buckets {
+    fn push(&mut self, node: OpenSetNode) {
+        let (total_cost, moved_cost) = (node.total_cost(), node.moved_cost);
+        if self.nodes.len() <= total_cost {
+            self.nodes.resize_with(total_cost + 1, Vec::new);
+            self.counts.resize(total_cost + 1, 0);
+        }
+        let layer = &mut self.nodes[total_cost];
+        if layer.len() <= moved_cost {
+            layer.resize_with(moved_cost + 1, Vec::new);
+        }
+        layer[moved_cost].push(node);
+        self.counts[total_cost] += 1;
+        self.lowest = self.lowest.min(total_cost);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<OpenSetNode> {
+        if self.len == 0 {
+            return None;
+        }
+        while self.counts[self.lowest] == 0 {
+            self.lowest += 1;
+        }
+        let layer = &mut self.nodes[self.lowest];
+        let node = layer.last_mut()?.pop()?;
+        // The last bucket of a layer is never empty, so that the node with the most moves is found at once
+        while layer.last().is_some_and(Vec::is_empty) {
+            layer.pop();
+        }
+        self.counts[self.lowest] -= 1;
+        self.len -= 1;
+        Some(node)
     }
 }
 
+#[derive(Debug)]
+enum Nodes {
+    Heap(BinaryHeap<OpenSetNode>),
+    Buckets(Buckets),
+}
+
 #[derive(Debug)]
 pub struct OpenSet {
-    set: BinaryHeap<OpenSetNode>,
+    set: Nodes,
     count: usize,
+    tie_break: Option<TieBreak>,
+    rng: Option<StdRng>,
 }
 
 impl OpenSet {
-    pub fn new() -> Self {
+    pub fn new(options: OpenOptions) -> Self {
+        let set = match options.list {
+            OpenList::Heap => Nodes::Heap(BinaryHeap::new()),
+            OpenList::Buckets => Nodes::Buckets(Buckets::default()),
+        };
+        let rng = match options.tie_break {
+            Some(TieBreak::Random(seed)) => Some(StdRng::seed_from_u64(seed)),
+            _ => None,
+        };
         Self {
-            set: BinaryHeap::new(),
+            set,
+            tie_break: options.tie_break,
+            rng,
             count: 0,
         }
     }
 
+    /// Open set holding the given nodes, after `count` insertions.
+    /// The nodes keep their order, and a random tie-break draws the next ones from a seed moved by `count`.
+    pub fn restore(options: OpenOptions, count: usize, nodes: Vec<OpenSetNode>) -> Self {
+        let mut open_set = Self::new(options);
+        if let Some(TieBreak::Random(seed)) = options.tie_break {
+            open_set.rng = Some(StdRng::seed_from_u64(seed.wrapping_add(count as u64)));
+        }
+        for node in nodes {
+            open_set.push(node);
+        }
+        open_set.count = count;
+        open_set
+    }
+
     pub fn get_append_count(&self) -> usize {
         self.count
     }
 
-    pub fn insert(&mut self, node: OpenSetNode) {
-        self.set.push(node);
+    pub fn insert(&mut self, mut node: OpenSetNode) {
+        node.tie = match self.tie_break {
+            None => 0,
+            Some(TieBreak::HigherG) => node.moved_cost as u64,
+            Some(TieBreak::LowerH) => u64::MAX - node.heuristics_cost as u64,
+            Some(TieBreak::Lifo) => self.count as u64,
+            Some(TieBreak::Fifo) => u64::MAX - self.count as u64,
+            Some(TieBreak::Random(_)) => self.rng.as_mut().map_or(0, |rng| rng.gen()),
+        };
+        self.push(node);
         self.count += 1;
     }
 
+    fn push(&mut self, node: OpenSetNode) {
+        match &mut self.set {
+            Nodes::Heap(heap) => heap.push(node),
+            Nodes::Buckets(buckets) => buckets.push(node),
+        }
+    }
+
+    /// Every node, in no particular order
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &OpenSetNode> + '_> {
+        match &self.set {
+            Nodes::Heap(heap) => Box::new(heap.iter()),
+            Nodes::Buckets(buckets) => Box::new(buckets.nodes.iter().flatten().flatten()),
+        }
+    }
+
     pub fn pop(&mut self) -> Option<OpenSetNode> {
-        self.set.pop()
+        match &mut self.set {
+            Nodes::Heap(heap) => heap.pop(),
+            Nodes::Buckets(buckets) => buckets.pop(),
+        }
     }
 
     pub fn len(&self) -> usize {
-        self.set.len()
+        match &self.set {
+            Nodes::Heap(heap) => heap.len(),
+            Nodes::Buckets(buckets) => buckets.len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::Heuristic;
+
+    /// Moves of the nodes in the order they are popped, for nodes of equal total cost
+    /// inserted with 8, 10, 6, 9 and 7 moves
+    fn pop_order(tie_break: TieBreak) -> anyhow::Result<Vec<usize>> {
+        // The blank is a wildcard, so that the heuristic only counts the tiles
+        let goal = Goal::from_text("3  1 2 3  8 * 4  7 6 5")?;
+        let heuristic = Heuristic::Manhattan.get_heuristic()?;
+        let mut open_set = OpenSet::new(OpenOptions {
+            list: OpenList::Heap,
+            tie_break: Some(tie_break),
+        });
+        // Each move takes one more tile one cell away from its goal
+        let mut states = vec![goal.target().clone()];
+        for m in [Move::Up, Move::Left, Move::Down, Move::Down] {
+            let mut state = states[states.len() - 1].clone();
+            state.move_blank(m)?;
+            states.push(state);
+        }
+        for distance in [2, 0, 4, 1, 3] {
+            let state = states[distance].clone();
+            open_set.insert(OpenSetNode::new(
+                state,
+                vec![],
+                10 - distance,
+                heuristic,
+                &goal,
+            ));
+        }
+        let mut order = Vec::new();
+        while let Some(node) = open_set.pop() {
+            assert_eq!(node.total_cost(), 10);
+            order.push(node.moved_cost());
+        }
+        Ok(order)
+    }
+
+    #[test]
+    fn test_tie_break() -> anyhow::Result<()> {
+        assert_eq!(pop_order(TieBreak::Fifo)?, vec![8, 10, 6, 9, 7]);
+        assert_eq!(pop_order(TieBreak::Lifo)?, vec![7, 9, 6, 10, 8]);
+        assert_eq!(pop_order(TieBreak::HigherG)?, vec![10, 9, 8, 7, 6]);
+        assert_eq!(pop_order(TieBreak::LowerH)?, vec![10, 9, 8, 7, 6]);
+        let mut order = pop_order(TieBreak::Random(7))?;
+        assert_eq!(order, pop_order(TieBreak::Random(7))?);
+        order.sort();
+        assert_eq!(order, vec![6, 7, 8, 9, 10]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_buckets() -> anyhow::Result<()> {
+        let goal = Goal::snail(3);
+        let heuristic = Heuristic::Manhattan.get_heuristic()?;
+        let mut open_set = OpenSet::new(OpenOptions {
+            list: OpenList::Buckets,
+            tie_break: None,
+        });
+        let mut state = goal.target().clone();
+        let mut expected = Vec::new();
+        for (i, m) in [Move::Up, Move::Left, Move::Down, Move::Down, Move::Right]
+            .into_iter()
+            .enumerate()
+        {
+            for moved in [i % 3, 2, 5 - i] {
+                let node = OpenSetNode::new(state.clone(), vec![], moved, heuristic, &goal);
+                expected.push((node.total_cost(), moved));
+                open_set.insert(node);
+            }
+            state.move_blank(m)?;
+        }
+        assert_eq!(open_set.len(), expected.len());
+        // Lowest total cost first, then the most moves
+        expected.sort_by_key(|&(total_cost, moved)| (total_cost, std::cmp::Reverse(moved)));
+        let mut order = Vec::new();
+        while let Some(node) = open_set.pop() {
+            order.push((node.total_cost(), node.moved_cost()));
+            // A node with a lower total cost than the popped ones is still found
+            if order.len() == 3 {
+                let node = OpenSetNode::new(goal.target().clone(), vec![], 0, heuristic, &goal);
+                open_set.insert(node);
+                expected.insert(3, (0, 0));
+            }
+        }
+        assert_eq!(order, expected);
+        assert_eq!(open_set.len(), 0);
+        Ok(())
     }
 }
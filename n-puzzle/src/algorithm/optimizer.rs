@@ -0,0 +1,225 @@
+use super::{astar, Heuristic, OpenOptions, Session, Timeout};
+use crate::{Goal, Move, Puzzle};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Number of moves re-solved at once by default
+pub const DEFAULT_WINDOW: usize = 16;
+/// Longest window, whose optimal search still fits in memory
+pub const MAX_WINDOW: usize = 32;
+
+/// Shortened path and the moves saved by each pass of the optimizer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Optimization {
+    pub path: Vec<Move>,
+    pub original_length: usize,
+    /// Moves removed by cancelling a move with the opposite one
+    pub backtracks_removed: usize,
+    /// Moves removed by cutting the path between two visits of the same state
+    pub cycles_removed: usize,
+    /// Moves saved by solving windows of the path optimally
+    pub window_saved: usize,
+}
+
+impl Optimization {
+    pub fn saved(&self) -> usize {
+        self.original_length - self.path.len()
+    }
+}
+
+impl std::fmt::Display for Optimization {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Moves saved by the optimizer: {} ({} -> {}: backtracks {}, cycles {}, windows {})",
+            self.saved(),
+            self.original_length,
+            self.path.len(),
+            self.backtracks_removed,
+            self.cycles_removed,
+            self.window_saved
+        )
+    }
+}
+
+/// Drop every move directly followed by the opposite move
+fn cancel_backtracks(path: &[Move]) -> Vec<Move> {
+    let mut kept: Vec<Move> = Vec::with_capacity(path.len());
+    for m in path {
+        match kept.last() {
+            Some(last) if last.opposite() == *m => {
+                kept.pop();
+            }
+            _ => kept.push(*m),
+        }
+    }
+    kept
+}
+
+/// Cut the moves between two visits of the same state
+fn remove_cycles(start: &Puzzle, path: &[Move]) -> Result<Vec<Move>> {
+    let mut kept: Vec<Move> = Vec::with_capacity(path.len());
+    let mut visited = HashMap::new();
+    let mut state = start.clone();
+    visited.insert(state.clone(), 0);
+    for m in path {
+        state.move_blank(*m)?;
+        match visited.get(&state) {
+            Some(&index) => {
+                // The states after the cut are not on the path anymore
+                kept.truncate(index);
+                visited.retain(|_, i| *i <= index);
+            }
+            None => {
+                kept.push(*m);
+                visited.insert(state.clone(), kept.len());
+            }
+        }
+    }
+    Ok(kept)
+}
+
+/// Solve every window of `window` moves optimally, moving the window by half its length.
+/// The windows are searched with an admissible heuristic: inversion_distance, or manhattan on a torus.
+/// The windows left when the timeout is reached keep their moves.
+fn optimize_windows(
+    start: &Puzzle,
+    path: &[Move],
+    window: usize,
+    timeout: Option<u64>,
+) -> Result<Vec<Move>> {
+    let started = Instant::now();
+    let mut path = path.to_vec();
+    let step = (window / 2).max(1);
+    let mut begin = 0;
+    // State before the move at `begin`
+    let mut from = start.clone();
+    while begin + 1 < path.len() {
+        let mut end = (begin + window).min(path.len());
+        let mut to = from.clone();
+        for m in &path[begin..end] {
+            to.move_blank(*m)?;
+        }
+        let goal = Goal::from_puzzle(to);
+        let heuristic = Heuristic::admissible(&goal, from.get_topology()).get_heuristic()?;
+        let remaining = timeout.map(|t| t.saturating_sub(started.elapsed().as_secs()));
+        let output = match astar(
+            from.clone(),
+            &goal,
+            heuristic,
+            false,
+            remaining,
+            OpenOptions::default(),
+            Session::default(),
+        ) {
+            Ok(output) => output,
+            Err(e) if e.is::<Timeout>() => break,
+            Err(e) => return Err(e),
+        };
+        if output.path.len() < end - begin {
+            let length = output.path.len();
+            path.splice(begin..end, output.path);
+            end = begin + length;
+        }
+        if end >= path.len() {
+            break;
+        }
+        let next = (begin + step).min(path.len());
+        for m in &path[begin..next] {
+            from.move_blank(*m)?;
+        }
+        begin = next;
+    }
+    Ok(path)
+}
+
+/// Shorten a solution without changing the state it leads to.
+///
+/// Back-and-forth moves are cancelled and cycles are removed first,
+/// then windows of `window` moves are replaced by optimal paths between their end states
+/// for as long as the timeout, in seconds, allows.
+pub fn optimize_path(
+    start: &Puzzle,
+    path: &[Move],
+    window: usize,
+    timeout: Option<u64>,
+) -> Result<Optimization> {
+    let cancelled = cancel_backtracks(path);
+    let acyclic = remove_cycles(start, &cancelled)?;
+    let optimized = optimize_windows(start, &acyclic, window, timeout)?;
+    Ok(Optimization {
+        original_length: path.len(),
+        backtracks_removed: path.len() - cancelled.len(),
+        cycles_removed: cancelled.len() - acyclic.len(),
+        window_saved: acyclic.len() - optimized.len(),
+        path: optimized,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(start: &Puzzle, path: &[Move]) -> Result<Puzzle> {
+        let mut state = start.clone();
+        for m in path {
+            state.move_blank(*m)?;
+        }
+        Ok(state)
+    }
+
+    #[test]
+    fn test_cancel_backtracks() {
+        let path = [Move::Up, Move::Left, Move::Right, Move::Down, Move::Left];
+        assert_eq!(cancel_backtracks(&path), vec![Move::Left]);
+    }
+
+    #[test]
+    fn test_remove_cycles() -> Result<()> {
+        let start = Puzzle::new_answer(3);
+        // Going three times around a 2x2 block comes back to the start
+        let mut path = Vec::new();
+        for _ in 0..3 {
+            path.extend([Move::Up, Move::Left, Move::Down, Move::Right]);
+        }
+        path.push(Move::Down);
+        assert_eq!(remove_cycles(&start, &path)?, vec![Move::Down]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_path() -> Result<()> {
+        let start = Puzzle::new_from_state(vec![vec![2, 8, 3], vec![1, 6, 4], vec![7, 0, 5]])?;
+        let goal = Heuristic::Exact.prepare_goal(&Goal::snail(3))?;
+        let optimal = astar::solve(
+            &start,
+            &goal,
+            Heuristic::Exact,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )?
+        .path;
+        let mut path = vec![Move::Left, Move::Right, Move::Up];
+        path.extend([Move::Up, Move::Left, Move::Down, Move::Right]);
+        path.push(Move::Down);
+        path.extend(&optimal);
+        let optimization = optimize_path(&start, &path, DEFAULT_WINDOW, None)?;
+        assert_eq!(apply(&start, &optimization.path)?, apply(&start, &path)?);
+        assert_eq!(optimization.path.len(), optimal.len());
+        assert!(optimization.backtracks_removed >= 2);
+        assert_eq!(optimization.saved(), path.len() - optimal.len());
+        assert_eq!(
+            optimization.backtracks_removed
+                + optimization.cycles_removed
+                + optimization.window_saved,
+            optimization.saved()
+        );
+        // Without time left, the windows keep their moves
+        let optimization = optimize_path(&start, &path, DEFAULT_WINDOW, Some(0))?;
+        assert_eq!(optimization.window_saved, 0);
+        assert_eq!(apply(&start, &optimization.path)?, apply(&start, &path)?);
+        Ok(())
+    }
+}
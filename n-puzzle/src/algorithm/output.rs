@@ -1,29 +1,153 @@
+mod html;
+
+use super::{Optimization, Solutions, TableStats, TieBreak};
 use crate::{Move, Puzzle};
 use anyhow::Result;
 
+/// Counters collected during a search
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchStats {
+    /// States added to the open set, including the start state
+    pub nodes_generated: usize,
+    pub nodes_expanded: usize,
+    /// States dropped because they were already reached with a lower or equal cost
+    pub duplicates: usize,
+    /// Closed states expanded again after being reached with a lower cost
+    pub reopened: usize,
+    pub max_open: usize,
+    pub max_closed: usize,
+    /// Peak number of states held in the open and closed sets together
+    pub max_memory: usize,
+    pub heuristic_evaluations: usize,
+}
+
+impl SearchStats {
+    pub fn update_peaks(&mut self, open: usize, closed: usize) {
+        self.max_open = self.max_open.max(open);
+        self.max_closed = self.max_closed.max(closed);
+        self.max_memory = self.max_memory.max(open + closed);
+    }
+}
+
+/// Moves made by one phase of a solver working in several phases
+#[derive(Debug, Clone, PartialEq)]
+pub struct Phase {
+    pub name: &'static str,
+    pub moves: usize,
+}
+
+impl Phase {
+    pub fn new(name: &'static str, moves: usize) -> Self {
+        Self { name, moves }
+    }
+}
+
 #[derive(Debug)]
 pub struct Output {
     pub complexity_in_time: usize,
     pub complexity_in_size: usize,
+    pub stats: SearchStats,
     pub elapsed_time: f64,
     pub path: Vec<Move>,
+    /// Every optimal solution, when requested
+    pub solutions: Option<Solutions>,
+    /// Moves saved by the path optimizer, when requested
+    pub optimization: Option<Optimization>,
+    /// Moves of each phase, empty for single phase algorithms
+    pub phases: Vec<Phase>,
+    /// Use of the transposition table, for the searches that keep one
+    pub transpositions: Option<TableStats>,
+    /// Nodes generated again after being forgotten, for the memory-bounded searches
+    pub regenerations: Option<usize>,
+    /// Order of the nodes of equal total cost, when one was chosen
+    pub tie_break: Option<TieBreak>,
 }
 
 impl Output {
-    pub fn new(
-        complexity_in_time: usize,
-        complexity_in_size: usize,
-        elapsed_time: f64,
-        path: Vec<Move>,
-    ) -> Self {
+    pub fn new(stats: SearchStats, elapsed_time: f64, path: Vec<Move>) -> Self {
         Self {
-            complexity_in_time,
-            complexity_in_size,
+            complexity_in_time: stats.nodes_generated,
+            complexity_in_size: stats.max_memory,
+            stats,
             elapsed_time,
             path,
+            solutions: None,
+            optimization: None,
+            phases: Vec::new(),
+            transpositions: None,
+            regenerations: None,
+            tie_break: None,
         }
     }
 
+    /// Heuristic penetrance: solution length divided by the number of generated nodes.
+    /// 1 means that only the nodes of the solution path were generated.
+    pub fn penetrance(&self) -> Option<f64> {
+        if self.complexity_in_time == 0 {
+            return None;
+        }
+        Some(self.path.len() as f64 / self.complexity_in_time as f64)
+    }
+
+    /// Search metrics as (label, value) pairs, shared by every output format
+    pub fn metrics(&self) -> Vec<(&'static str, String)> {
+        let ratio = |value: Option<f64>| match value {
+            Some(value) => format!("{:.6}", value),
+            None => String::from("-"),
+        };
+        vec![
+            ("Nodes generated", self.stats.nodes_generated.to_string()),
+            ("Nodes expanded", self.stats.nodes_expanded.to_string()),
+            ("Duplicates discarded", self.stats.duplicates.to_string()),
+            ("Re-openings", self.stats.reopened.to_string()),
+            ("Max open set size", self.stats.max_open.to_string()),
+            ("Max closed set size", self.stats.max_closed.to_string()),
+            (
+                "Heuristic evaluations",
+                self.stats.heuristic_evaluations.to_string(),
+            ),
+            (
+                "Effective branching factor",
+                ratio(self.effective_branching_factor()),
+            ),
+            ("Heuristic penetrance", ratio(self.penetrance())),
+        ]
+    }
+
+    /// Effective branching factor b* such that a uniform tree of depth d
+    /// with branching factor b* contains as many nodes as were generated:
+    /// N = b* + b*^2 + ... + b*^d
+    pub fn effective_branching_factor(&self) -> Option<f64> {
+        let depth = self.path.len();
+        if depth == 0 {
+            return None;
+        }
+        let nodes = self.complexity_in_time as f64;
+        let tree_size = |b: f64| {
+            let mut total = 0.0;
+            let mut level = 1.0;
+            for _ in 0..depth {
+                level *= b;
+                total += level;
+                if total > nodes {
+                    break;
+                }
+            }
+            total
+        };
+        let mut low = 0.0;
+        let mut high = nodes.max(1.0);
+        for _ in 0..100 {
+            let mid = (low + high) / 2.0;
+            if tree_size(mid) < nodes {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Some((low + high) / 2.0)
+    }
+
     fn verbose_output(&self, mut text: String, mut puzzle: Puzzle) -> Result<String> {
         text += format!("{}", puzzle).as_str();
         for m in &self.path {
@@ -50,6 +174,27 @@ impl Output {
         text += format!("Complexity in size: {}\n", self.complexity_in_size).as_str();
         text += format!("Elapsed time: {:.6} seconds\n", self.elapsed_time).as_str();
         text += format!("Number of moves: {}\n", self.path.len()).as_str();
+        for phase in &self.phases {
+            text += format!("Moves in the {}: {}\n", phase.name, phase.moves).as_str();
+        }
+        for (label, value) in self.metrics() {
+            text += format!("{}: {}\n", label, value).as_str();
+        }
+        if let Some(regenerations) = self.regenerations {
+            text += format!("Regenerations: {}\n", regenerations).as_str();
+        }
+        if let Some(tie_break) = self.tie_break {
+            text += format!("Tie-breaking: {}\n", tie_break).as_str();
+        }
+        if let Some(transpositions) = &self.transpositions {
+            text += format!("{}", transpositions).as_str();
+        }
+        if let Some(optimization) = &self.optimization {
+            text += format!("{}", optimization).as_str();
+        }
+        if let Some(solutions) = &self.solutions {
+            text += format!("{}", solutions).as_str();
+        }
         let text = if verbose {
             self.verbose_output(text, puzzle)?
         } else {
@@ -69,9 +214,17 @@ impl Output {
 mod tests {
     use super::*;
 
+    pub(super) fn stats(nodes_generated: usize, max_memory: usize) -> SearchStats {
+        SearchStats {
+            nodes_generated,
+            max_memory,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_output_verbose() -> Result<()> {
-        let output = Output::new(1, 1, 1.0, vec![Move::Right]);
+        let output = Output::new(stats(1, 1), 1.0, vec![Move::Right]);
         let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 0, 8]])?;
         let text = output.get_result_string(puzzle, true)?;
         assert_eq!(
@@ -80,6 +233,15 @@ mod tests {
 Complexity in size: 1
 Elapsed time: 1.000000 seconds
 Number of moves: 1
+Nodes generated: 1
+Nodes expanded: 0
+Duplicates discarded: 0
+Re-openings: 0
+Max open set size: 0
+Max closed set size: 0
+Heuristic evaluations: 0
+Effective branching factor: 1.000000
+Heuristic penetrance: 1.000000
 1 2 3
 4 5 6
 7 0 8
@@ -94,7 +256,7 @@ Number of moves: 1
 
     #[test]
     fn test_output_non_verbose() -> Result<()> {
-        let output = Output::new(1, 1, 2.0, vec![Move::Up, Move::Left]);
+        let output = Output::new(stats(1, 1), 2.0, vec![Move::Up, Move::Left]);
         let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![4, 0, 5], vec![7, 8, 6]])?;
         let text = output.get_result_string(puzzle, false)?;
         assert_eq!(
@@ -103,6 +265,44 @@ Number of moves: 1
 Complexity in size: 1
 Elapsed time: 2.000000 seconds
 Number of moves: 2
+Nodes generated: 1
+Nodes expanded: 0
+Duplicates discarded: 0
+Re-openings: 0
+Max open set size: 0
+Max closed set size: 0
+Heuristic evaluations: 0
+Effective branching factor: 0.618034
+Heuristic penetrance: 2.000000
+1 2 3
+4 0 5
+7 8 6
+Moves: Up Left 
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_search_metrics() -> Result<()> {
+        let output = Output::new(stats(4, 3), 2.0, vec![Move::Up, Move::Left]);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![4, 0, 5], vec![7, 8, 6]])?;
+        let text = output.get_result_string(puzzle, false)?;
+        assert_eq!(
+            text,
+            "Complexity in time: 4
+Complexity in size: 3
+Elapsed time: 2.000000 seconds
+Number of moves: 2
+Nodes generated: 4
+Nodes expanded: 0
+Duplicates discarded: 0
+Re-openings: 0
+Max open set size: 0
+Max closed set size: 0
+Heuristic evaluations: 0
+Effective branching factor: 1.561553
+Heuristic penetrance: 0.500000
 1 2 3
 4 0 5
 7 8 6
@@ -111,4 +311,32 @@ Moves: Up Left
         );
         Ok(())
     }
+
+    #[test]
+    fn test_effective_branching_factor() {
+        let output = Output::new(stats(14, 10), 1.0, vec![Move::Up, Move::Left, Move::Down]);
+        // 2 + 4 + 8 = 14
+        let ebf = output.effective_branching_factor().unwrap();
+        assert!((ebf - 2.0).abs() < 1e-9);
+        let output = Output::new(stats(1, 1), 1.0, vec![]);
+        assert_eq!(output.effective_branching_factor(), None);
+    }
+
+    #[test]
+    fn test_penetrance() {
+        let output = Output::new(stats(8, 8), 1.0, vec![Move::Up, Move::Left]);
+        assert_eq!(output.penetrance(), Some(0.25));
+        let output = Output::new(stats(0, 0), 1.0, vec![]);
+        assert_eq!(output.penetrance(), None);
+    }
+
+    #[test]
+    fn test_update_peaks() {
+        let mut stats = SearchStats::default();
+        stats.update_peaks(5, 1);
+        stats.update_peaks(2, 3);
+        assert_eq!(stats.max_open, 5);
+        assert_eq!(stats.max_closed, 3);
+        assert_eq!(stats.max_memory, 6);
+    }
 }
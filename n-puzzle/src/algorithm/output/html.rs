@@ -0,0 +1,153 @@
+use super::Output;
+use crate::Puzzle;
+use anyhow::Result;
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+td { padding: 2px 12px 2px 0; }
+#player { margin-bottom: 2em; }
+#controls { margin-top: 0.5em; }
+#controls button { min-width: 3em; }
+#filmstrip { display: flex; flex-wrap: wrap; gap: 12px; }
+.frame { margin: 0; padding: 4px; border: 2px solid transparent; cursor: pointer; }
+.frame.current { border-color: #d33; }
+.frame figcaption { text-align: center; font-size: 0.9em; }
+.frame svg { display: block; max-width: 160px; height: auto; }";
+
+const SCRIPT: &str = "const frames = document.querySelectorAll('.frame');
+const board = document.getElementById('board');
+const slider = document.getElementById('slider');
+const label = document.getElementById('label');
+const play = document.getElementById('play');
+let current = 0;
+let timer = null;
+function show(index) {
+  current = Math.max(0, Math.min(frames.length - 1, index));
+  frames.forEach((frame, i) => frame.classList.toggle('current', i === current));
+  board.innerHTML = frames[current].querySelector('svg').outerHTML;
+  slider.value = current;
+  label.textContent = 'Step ' + current + ' / ' + (frames.length - 1) + ': ' + frames[current].dataset.move;
+}
+function stop() {
+  clearInterval(timer);
+  timer = null;
+  play.textContent = 'Play';
+}
+document.getElementById('first').onclick = () => { stop(); show(0); };
+document.getElementById('prev').onclick = () => { stop(); show(current - 1); };
+document.getElementById('next').onclick = () => { stop(); show(current + 1); };
+document.getElementById('last').onclick = () => { stop(); show(frames.length - 1); };
+play.onclick = () => {
+  if (timer) {
+    stop();
+    return;
+  }
+  if (current === frames.length - 1) {
+    show(0);
+  }
+  play.textContent = 'Pause';
+  timer = setInterval(() => {
+    if (current === frames.length - 1) {
+      stop();
+    } else {
+      show(current + 1);
+    }
+  }, 500);
+};
+slider.oninput = () => { stop(); show(Number(slider.value)); };
+frames.forEach((frame, i) => frame.onclick = () => { stop(); show(i); });
+show(0);";
+
+impl Output {
+    fn html_frame(puzzle: &Puzzle, index: usize, caption: &str) -> String {
+        format!(
+            "<figure class=\"frame\" data-move=\"{1}\">{2}<figcaption>{0}: {1}</figcaption></figure>\n",
+            index,
+            caption,
+            puzzle.to_svg()
+        )
+    }
+
+    /// Render the solution as a self-contained HTML page with a filmstrip and a player
+    pub fn to_html(&self, mut puzzle: Puzzle) -> Result<String> {
+        let mut filmstrip = Self::html_frame(&puzzle, 0, "Start");
+        for (i, m) in self.path.iter().enumerate() {
+            puzzle.move_blank(*m)?;
+            filmstrip += Self::html_frame(&puzzle, i + 1, format!("{:?}", m).as_str()).as_str();
+        }
+
+        let mut html = String::new();
+        html += "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n";
+        html += "<title>N-Puzzle solution</title>\n";
+        html += format!("<style>\n{}\n</style>\n", STYLE).as_str();
+        html += "</head>\n<body>\n<h1>N-Puzzle solution</h1>\n<table>\n";
+        html += format!(
+            "<tr><td>Complexity in time</td><td>{}</td></tr>\n",
+            self.complexity_in_time
+        )
+        .as_str();
+        html += format!(
+            "<tr><td>Complexity in size</td><td>{}</td></tr>\n",
+            self.complexity_in_size
+        )
+        .as_str();
+        html += format!(
+            "<tr><td>Elapsed time</td><td>{:.6} seconds</td></tr>\n",
+            self.elapsed_time
+        )
+        .as_str();
+        html += format!(
+            "<tr><td>Number of moves</td><td>{}</td></tr>\n",
+            self.path.len()
+        )
+        .as_str();
+        for (label, value) in self.metrics() {
+            html += format!("<tr><td>{}</td><td>{}</td></tr>\n", label, value).as_str();
+        }
+        html += "</table>\n<div id=\"player\">\n<div id=\"board\"></div>\n<div id=\"controls\">\n";
+        html += "<button id=\"first\">|&lt;</button> <button id=\"prev\">&lt;</button> ";
+        html += "<button id=\"play\">Play</button> ";
+        html += "<button id=\"next\">&gt;</button> <button id=\"last\">&gt;|</button>\n";
+        html += format!(
+            "<input id=\"slider\" type=\"range\" min=\"0\" max=\"{}\" value=\"0\">\n",
+            self.path.len()
+        )
+        .as_str();
+        html += "<span id=\"label\"></span>\n</div>\n</div>\n";
+        html += format!("<div id=\"filmstrip\">\n{}</div>\n", filmstrip).as_str();
+        html += format!("<script>\n{}\n</script>\n", SCRIPT).as_str();
+        html += "</body>\n</html>\n";
+        Ok(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::output::tests::stats;
+    use crate::Move;
+
+    #[test]
+    fn test_to_html() -> Result<()> {
+        let output = Output::new(stats(2, 2), 1.0, vec![Move::Up, Move::Left]);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![4, 0, 5], vec![7, 8, 6]])?;
+        let html = output.to_html(puzzle)?;
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert_eq!(html.matches("<figure class=\"frame\"").count(), 3);
+        assert!(html.contains("<figcaption>0: Start</figcaption>"));
+        assert!(html.contains("<figcaption>2: Left</figcaption>"));
+        assert!(html.contains("max=\"2\""));
+        assert!(html.contains("<tr><td>Heuristic penetrance</td><td>1.000000</td></tr>"));
+        assert!(html.contains("<script>"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_html_invalid_path() -> Result<()> {
+        let output = Output::new(stats(1, 1), 1.0, vec![Move::Up, Move::Up]);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![4, 0, 5], vec![7, 8, 6]])?;
+        assert!(output.to_html(puzzle).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use super::{Heuristic, Output, SearchStats, Timeout};
+use crate::{Goal, Move, Puzzle};
+use anyhow::{anyhow, Result};
+use std::time::{Duration, Instant};
+
+/// Cost of a subtree without any solution
+const INFINITY: usize = usize::MAX;
+
+enum Outcome {
+    Found,
+    /// Lowest cost of the frontier of the subtree, which becomes its backed-up cost
+    Failed(usize),
+}
+
+struct Child {
+    m: Move,
+    /// Cost of the child itself
+    static_cost: usize,
+    /// Cost backed up from its subtree by the previous visits
+    cost: usize,
+    visits: usize,
+}
+
+struct Rbfs<'a> {
+    goal: &'a Goal,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
+    path: Vec<Move>,
+    stats: SearchStats,
+    regenerations: usize,
+    /// Children held by the states of the current path
+    stored: usize,
+    start: Instant,
+    timeout: Option<Duration>,
+}
+
+impl Rbfs<'_> {
+    /// Search below `puzzle` until the goal is found or every frontier cost exceeds `bound`.
+    /// `cost` is the backed-up cost of the state and `static_cost` its own cost.
+    fn search(
+        &mut self,
+        puzzle: &mut Puzzle,
+        moved: usize,
+        cost: usize,
+        static_cost: usize,
+        bound: usize,
+    ) -> Result<Outcome> {
+        if self.goal.is_reached(puzzle) {
+            return Ok(Outcome::Found);
+        }
+        if let Some(duration) = self.timeout {
+            if self.start.elapsed() > duration {
+                return Err(Timeout.into());
+            }
+        }
+        self.stats.nodes_expanded += 1;
+        let mut children = Vec::new();
+        for m in Move::list() {
+            if self.path.last() == Some(&m.opposite()) || puzzle.move_blank(m).is_err() {
+                continue;
+            }
+            let child_cost = moved + 1 + (self.heuristic)(puzzle, self.goal);
+            puzzle.move_blank(m.opposite())?;
+            self.stats.heuristic_evaluations += 1;
+            self.stats.nodes_generated += 1;
+            // A state searched before passes its backed-up cost on to its children
+            let inherited = if cost > static_cost {
+                cost.max(child_cost)
+            } else {
+                child_cost
+            };
+            children.push(Child {
+                m,
+                static_cost: child_cost,
+                cost: inherited,
+                visits: 0,
+            });
+        }
+        if children.is_empty() {
+            return Ok(Outcome::Failed(INFINITY));
+        }
+        self.stored += children.len();
+        self.stats.max_memory = self.stats.max_memory.max(self.stored);
+        loop {
+            // The first lowest cost child, and the lowest cost among the others
+            let best = (0..children.len())
+                .min_by_key(|&i| children[i].cost)
+                .unwrap();
+            if children[best].cost > bound {
+                self.stored -= children.len();
+                return Ok(Outcome::Failed(children[best].cost));
+            }
+            let alternative = (0..children.len())
+                .filter(|&i| i != best)
+                .map(|i| children[i].cost)
+                .min()
+                .unwrap_or(INFINITY);
+            let child = &mut children[best];
+            if child.visits > 0 {
+                self.regenerations += 1;
+            }
+            child.visits += 1;
+            let (m, cost, static_cost) = (child.m, child.cost, child.static_cost);
+            puzzle.move_blank(m)?;
+            self.path.push(m);
+            let outcome =
+                self.search(puzzle, moved + 1, cost, static_cost, bound.min(alternative))?;
+            match outcome {
+                Outcome::Found => return Ok(Outcome::Found),
+                Outcome::Failed(cost) => children[best].cost = cost,
+            }
+            self.path.pop();
+            puzzle.move_blank(m.opposite())?;
+        }
+    }
+}
+
+/// Recursive best-first search: optimal with an admissible heuristic and memory linear in the solution length.
+///
+/// Only the children of the states on the current path are kept, each with the lowest cost backed up
+/// from its forgotten subtree. A subtree searched again after being abandoned counts as a regeneration.
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+) -> Result<Output> {
+    let mut search = Rbfs {
+        goal,
+        heuristic: heuristic.get_heuristic()?,
+        path: Vec::new(),
+        stats: SearchStats {
+            nodes_generated: 1,
+            heuristic_evaluations: 1,
+            ..Default::default()
+        },
+        regenerations: 0,
+        stored: 0,
+        start: Instant::now(),
+        timeout: timeout.map(|t| Duration::new(t, 0)),
+    };
+    let cost = (search.heuristic)(puzzle, goal);
+    match search.search(&mut puzzle.clone(), 0, cost, cost, INFINITY)? {
+        Outcome::Found => {}
+        Outcome::Failed(_) => return Err(anyhow!("No solution")),
+    }
+    let mut output = Output::new(
+        search.stats,
+        search.start.elapsed().as_secs_f64(),
+        search.path,
+    );
+    output.regenerations = Some(search.regenerations);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{astar, OpenOptions, Session};
+
+    #[test]
+    fn test_rbfs_optimal() -> Result<()> {
+        let goal = Goal::snail(3);
+        for _ in 0..5 {
+            let puzzle = Puzzle::generate_solvable_to(3, &goal)?;
+            let output = solve(&puzzle, &goal, Heuristic::Manhattan, None)?;
+            let expected = astar::solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                OpenOptions::default(),
+                Session::default(),
+            )?;
+            assert_eq!(output.path.len(), expected.path.len());
+            let mut state = puzzle.clone();
+            for m in &output.path {
+                state.move_blank(*m)?;
+            }
+            assert!(goal.is_reached(&state));
+            assert!(output.stats.max_memory <= 3 * output.path.len().max(1) + 1);
+            assert!(output.regenerations.is_some());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rbfs_solved() -> Result<()> {
+        let goal = Goal::snail(3);
+        let output = solve(goal.target(), &goal, Heuristic::Manhattan, None)?;
+        assert!(output.path.is_empty());
+        assert_eq!(output.regenerations, Some(0));
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+use super::{Heuristic, Output, SearchStats, Timeout};
+use crate::{Goal, Move, Puzzle};
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+/// Nodes kept in memory by default
+pub const DEFAULT_NODE_BUDGET: usize = 100_000;
+
+/// Cost of a node that cannot lead to the goal within the budget
+const INFINITY: usize = usize::MAX;
+
+struct Node {
+    puzzle: Puzzle,
+    parent: Option<usize>,
+    /// Move from the parent
+    m: Option<Move>,
+    moved: usize,
+    cost: usize,
+    children: Vec<usize>,
+    /// Lowest cost of the children dropped to free memory
+    forgotten: usize,
+    expanded: bool,
+}
+
+/// Open nodes ordered by cost, the deepest first among equal costs
+type Leaf = (usize, Reverse<usize>, usize);
+
+struct SmaStar<'a> {
+    goal: &'a Goal,
+    heuristic: fn(&Puzzle, &Goal) -> usize,
+    budget: usize,
+    nodes: Vec<Option<Node>>,
+    free: Vec<usize>,
+    live: usize,
+    /// Leaves, and expanded nodes with forgotten children to generate again
+    open: BTreeSet<Leaf>,
+    stats: SearchStats,
+    regenerations: usize,
+}
+
+impl SmaStar<'_> {
+    fn node(&self, id: usize) -> &Node {
+        self.nodes[id].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, id: usize) -> &mut Node {
+        self.nodes[id].as_mut().unwrap()
+    }
+
+    /// Key of a node in the open set: its own cost for a leaf,
+    /// the lowest cost of its forgotten children otherwise
+    fn key(&self, id: usize) -> Option<Leaf> {
+        let node = self.node(id);
+        if node.children.is_empty() {
+            Some((node.cost, Reverse(node.moved), id))
+        } else if node.forgotten != INFINITY {
+            Some((node.cost.max(node.forgotten), Reverse(node.moved), id))
+        } else {
+            None
+        }
+    }
+
+    fn open(&mut self, id: usize) {
+        if let Some(key) = self.key(id) {
+            self.open.insert(key);
+        }
+    }
+
+    fn close(&mut self, id: usize) {
+        if let Some(key) = self.key(id) {
+            self.open.remove(&key);
+        }
+    }
+
+    fn add(&mut self, node: Node) -> usize {
+        self.live += 1;
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = Some(node);
+                id
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Drop the shallowest of the highest cost leaves, other than `keep` and its children,
+    /// remembering its cost in its parent
+    fn forget_worst(&mut self, keep: usize) -> bool {
+        let worst = self.open.iter().rev().copied().find(|&(_, _, id)| {
+            let node = self.node(id);
+            id != keep
+                && node.children.is_empty()
+                && node.parent.is_some_and(|parent| parent != keep)
+        });
+        let (cost, _, id) = match worst {
+            Some(leaf) => leaf,
+            None => return false,
+        };
+        let parent = self.node(id).parent.unwrap();
+        self.close(id);
+        self.nodes[id] = None;
+        self.free.push(id);
+        self.live -= 1;
+        self.close(parent);
+        let node = self.node_mut(parent);
+        node.children.retain(|&child| child != id);
+        node.forgotten = node.forgotten.min(cost);
+        if node.children.is_empty() {
+            // Every child is forgotten: the parent is a leaf again, with the best cost seen below it
+            node.cost = node.cost.max(node.forgotten);
+        }
+        self.open(parent);
+        true
+    }
+
+    fn path(&self, mut id: usize) -> Vec<Move> {
+        let mut path = Vec::new();
+        while let Some(m) = self.node(id).m {
+            path.push(m);
+            id = self.node(id).parent.unwrap();
+        }
+        path.reverse();
+        path
+    }
+
+    /// Generate the children of an open node that are not in memory, making room for them first
+    fn expand(&mut self, id: usize) -> Result<()> {
+        let (puzzle, moved, cost, back) = {
+            let node = self.node(id);
+            (
+                node.puzzle.clone(),
+                node.moved,
+                // Forgotten children come back with the cost backed up from them
+                match node.forgotten {
+                    INFINITY => node.cost,
+                    forgotten => node.cost.max(forgotten),
+                },
+                node.m.map(|m| m.opposite()),
+            )
+        };
+        let present: Vec<Move> = self
+            .node(id)
+            .children
+            .iter()
+            .filter_map(|&child| self.node(child).m)
+            .collect();
+        let mut children = Vec::new();
+        for m in Move::list() {
+            if Some(m) == back || present.contains(&m) {
+                continue;
+            }
+            let mut next = puzzle.clone();
+            if next.move_blank(m).is_ok() {
+                children.push((m, next));
+            }
+        }
+        self.close(id);
+        while self.live + children.len() > self.budget {
+            if !self.forget_worst(id) {
+                break;
+            }
+        }
+        if self.live + children.len() > self.budget {
+            // Too deep for the budget: the missing children cannot lead to the goal
+            let node = self.node_mut(id);
+            if node.children.is_empty() {
+                node.cost = INFINITY;
+            }
+            node.forgotten = INFINITY;
+            self.open(id);
+            return Ok(());
+        }
+        self.stats.nodes_expanded += 1;
+        if self.node(id).expanded {
+            self.regenerations += children.len();
+        }
+        let node = self.node_mut(id);
+        node.expanded = true;
+        node.forgotten = INFINITY;
+        for (m, next) in children {
+            let estimate = (self.heuristic)(&next, self.goal);
+            self.stats.heuristic_evaluations += 1;
+            self.stats.nodes_generated += 1;
+            let child = self.add(Node {
+                puzzle: next,
+                parent: Some(id),
+                m: Some(m),
+                moved: moved + 1,
+                // The cost never decreases along a path
+                cost: cost.max(moved + 1 + estimate),
+                children: Vec::new(),
+                forgotten: INFINITY,
+                expanded: false,
+            });
+            self.node_mut(id).children.push(child);
+            self.open(child);
+        }
+        Ok(())
+    }
+}
+
+/// Simplified memory-bounded A*: A* on the search tree that never holds more than `budget` nodes.
+///
+/// When memory is full, the worst leaf is dropped and its cost is kept in its parent, which is expanded again
+/// if it becomes the best leaf. The path is optimal with an admissible heuristic when the budget can hold it.
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    budget: usize,
+    timeout: Option<u64>,
+) -> Result<Output> {
+    let heuristic = heuristic.get_heuristic()?;
+    let start = Instant::now();
+    let timeout = timeout.map(|t| Duration::new(t, 0));
+    let mut search = SmaStar {
+        goal,
+        heuristic,
+        budget: budget.max(1),
+        nodes: Vec::new(),
+        free: Vec::new(),
+        live: 0,
+        open: BTreeSet::new(),
+        stats: SearchStats {
+            nodes_generated: 1,
+            heuristic_evaluations: 1,
+            ..Default::default()
+        },
+        regenerations: 0,
+    };
+    let root = search.add(Node {
+        puzzle: puzzle.clone(),
+        parent: None,
+        m: None,
+        moved: 0,
+        cost: heuristic(puzzle, goal),
+        children: Vec::new(),
+        forgotten: INFINITY,
+        expanded: false,
+    });
+    search.open(root);
+    while let Some(&best) = search.open.first() {
+        if let Some(duration) = timeout {
+            if start.elapsed() > duration {
+                return Err(Timeout.into());
+            }
+        }
+        if best.0 == INFINITY {
+            break;
+        }
+        let id = best.2;
+        if search.node(id).children.is_empty() && goal.is_reached(&search.node(id).puzzle) {
+            let mut output =
+                Output::new(search.stats, start.elapsed().as_secs_f64(), search.path(id));
+            output.regenerations = Some(search.regenerations);
+            return Ok(output);
+        }
+        search.expand(id)?;
+        let open = search.open.len();
+        search.stats.update_peaks(open, search.live - open);
+    }
+    Err(anyhow!("No solution within the node budget"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{astar, OpenOptions, Session};
+
+    fn check(puzzle: &Puzzle, goal: &Goal, budget: usize) -> Result<Output> {
+        let output = solve(puzzle, goal, Heuristic::Manhattan, budget, None)?;
+        let mut state = puzzle.clone();
+        for m in &output.path {
+            state.move_blank(*m)?;
+        }
+        assert!(goal.is_reached(&state));
+        assert!(output.stats.max_memory <= budget);
+        Ok(output)
+    }
+
+    #[test]
+    fn test_sma_star_optimal() -> Result<()> {
+        let goal = Goal::snail(3);
+        let states = [
+            vec![vec![2, 8, 3], vec![1, 6, 4], vec![7, 0, 5]],
+            vec![vec![0, 1, 3], vec![8, 2, 4], vec![7, 6, 5]],
+            vec![vec![1, 2, 3], vec![0, 8, 4], vec![7, 6, 5]],
+        ];
+        for state in states {
+            let puzzle = Puzzle::new_from_state(state)?;
+            let expected = astar::solve(
+                &puzzle,
+                &goal,
+                Heuristic::Manhattan,
+                None,
+                OpenOptions::default(),
+                Session::default(),
+            )?;
+            for budget in [200, DEFAULT_NODE_BUDGET] {
+                let output = check(&puzzle, &goal, budget)?;
+                assert_eq!(output.path.len(), expected.path.len());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sma_star_regenerations() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![8, 7, 6], vec![1, 0, 5], vec![2, 3, 4]])?;
+        let small = check(&puzzle, &goal, 150)?;
+        let large = check(&puzzle, &goal, DEFAULT_NODE_BUDGET)?;
+        assert_eq!(small.path.len(), large.path.len());
+        assert!(small.regenerations.unwrap() > 0);
+        assert_eq!(large.regenerations, Some(0));
+        assert!(solve(&puzzle, &goal, Heuristic::Manhattan, 4, None).is_err());
+        Ok(())
+    }
+}
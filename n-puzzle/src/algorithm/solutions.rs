@@ -0,0 +1,194 @@
+use super::Heuristic;
+use crate::{Goal, Move, Puzzle};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Every optimal solution of a puzzle, or only their number
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solutions {
+    pub length: usize,
+    /// Number of distinct optimal move sequences, saturating at `u64::MAX`
+    pub count: u64,
+    /// Optimal move sequences in move order, at most the requested cap
+    pub paths: Vec<Vec<Move>>,
+}
+
+impl std::fmt::Display for Solutions {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Optimal solutions: {}", self.count)?;
+        for path in &self.paths {
+            let moves: Vec<String> = path.iter().map(|m| format!("{:?}", m)).collect();
+            writeln!(f, "  {}", moves.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Length and number of the shortest solutions of a state
+type Completion = Option<(usize, u64)>;
+
+struct Enumerator<'a> {
+    goal: &'a Goal,
+    heuristic: Option<fn(&Puzzle, &Goal) -> usize>,
+    bound: usize,
+    /// Shortest solutions of the states reached after a number of moves
+    memo: HashMap<(Puzzle, usize), Completion>,
+}
+
+impl Enumerator<'_> {
+    /// Shortest solutions of `puzzle` within the moves left after `moved` moves
+    fn search(&mut self, puzzle: &Puzzle, moved: usize) -> Completion {
+        if self.goal.is_reached(puzzle) {
+            return Some((0, 1));
+        }
+        let estimate = self
+            .heuristic
+            .map_or(0, |heuristic| heuristic(puzzle, self.goal));
+        if moved + estimate > self.bound {
+            return None;
+        }
+        let key = (puzzle.clone(), moved);
+        if let Some(completion) = self.memo.get(&key) {
+            return *completion;
+        }
+        let mut best: Completion = None;
+        for m in Move::list() {
+            let mut next = puzzle.clone();
+            if next.move_blank(m).is_err() {
+                continue;
+            }
+            if let Some((length, count)) = self.search(&next, moved + 1) {
+                best = match best {
+                    Some((best_length, _)) if best_length < length + 1 => best,
+                    Some((best_length, best_count)) if best_length == length + 1 => {
+                        Some((best_length, best_count.saturating_add(count)))
+                    }
+                    _ => Some((length + 1, count)),
+                };
+            }
+        }
+        self.memo.insert(key, best);
+        best
+    }
+
+    fn completion(&self, puzzle: &Puzzle, moved: usize) -> Completion {
+        if self.goal.is_reached(puzzle) {
+            return Some((0, 1));
+        }
+        self.memo.get(&(puzzle.clone(), moved)).copied().flatten()
+    }
+
+    /// Collect the shortest solutions found by `search`, up to `cap` paths
+    fn collect(
+        &self,
+        puzzle: &Puzzle,
+        moved: usize,
+        path: &mut Vec<Move>,
+        paths: &mut Vec<Vec<Move>>,
+        cap: usize,
+    ) {
+        let length = match self.completion(puzzle, moved) {
+            Some((0, _)) => {
+                paths.push(path.clone());
+                return;
+            }
+            Some((length, _)) => length,
+            None => return,
+        };
+        for m in Move::list() {
+            if paths.len() >= cap {
+                return;
+            }
+            let mut next = puzzle.clone();
+            if next.move_blank(m).is_err() {
+                continue;
+            }
+            if let Some((next_length, _)) = self.completion(&next, moved + 1) {
+                if next_length + 1 == length {
+                    path.push(m);
+                    self.collect(&next, moved + 1, path, paths, cap);
+                    path.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Count the optimal solutions of a puzzle, and list up to `cap` of them.
+///
+/// `bound` is the length of a known solution. Solutions longer than it are pruned with the heuristic,
+/// so the heuristic must be admissible for every optimal solution to be found; with none,
+/// they are only pruned by length.
+pub fn optimal_solutions(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    heuristic: Heuristic,
+    bound: usize,
+    cap: Option<usize>,
+) -> Result<Solutions> {
+    let heuristic = match heuristic {
+        Heuristic::None => None,
+        heuristic => Some(heuristic.get_heuristic()?),
+    };
+    let mut enumerator = Enumerator {
+        goal,
+        heuristic,
+        bound,
+        memo: HashMap::new(),
+    };
+    let (length, count) = enumerator
+        .search(puzzle, 0)
+        .ok_or_else(|| anyhow!("No solution within {} moves", bound))?;
+    let mut paths = Vec::new();
+    if let Some(cap) = cap {
+        enumerator.collect(puzzle, 0, &mut Vec::new(), &mut paths, cap);
+    }
+    Ok(Solutions {
+        length,
+        count,
+        paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimal_solutions() -> Result<()> {
+        let goal = Goal::snail(2);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 0], vec![1, 3]])?;
+        let solutions =
+            optimal_solutions(&puzzle, &goal, Heuristic::InversionDistance, 2, Some(10))?;
+        assert_eq!(solutions.length, 2);
+        assert_eq!(solutions.count, 1);
+        assert_eq!(solutions.paths, vec![vec![Move::Left, Move::Down]]);
+        let solutions = optimal_solutions(&puzzle, &goal, Heuristic::None, 2, None)?;
+        assert_eq!((solutions.length, solutions.count), (2, 1));
+        let solutions = optimal_solutions(&goal.target().clone(), &goal, Heuristic::None, 0, None)?;
+        assert_eq!((solutions.length, solutions.count), (0, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_matches_paths() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 8, 3], vec![1, 6, 4], vec![7, 0, 5]])?;
+        // A longer bound still finds the optimal length
+        let solutions = optimal_solutions(&puzzle, &goal, Heuristic::Manhattan, 9, Some(100))?;
+        assert_eq!(solutions.length, 5);
+        assert_eq!(solutions.count as usize, solutions.paths.len());
+        let capped = optimal_solutions(&puzzle, &goal, Heuristic::Exact, 5, Some(1))?;
+        assert_eq!(capped.count, solutions.count);
+        assert_eq!(capped.paths, solutions.paths[..1].to_vec());
+        for path in &solutions.paths {
+            let mut state = puzzle.clone();
+            for m in path {
+                state.move_blank(*m)?;
+            }
+            assert!(goal.is_reached(&state));
+        }
+        assert!(optimal_solutions(&puzzle, &goal, Heuristic::Manhattan, 3, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::Puzzle;
+
+/// Which entry a transposition table keeps when two states share a slot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Replacement {
+    /// The latest state replaces the stored one
+    Always,
+    /// The state searched with the most moves left is kept, as it saves the largest subtree
+    Depth,
+}
+
+impl std::fmt::Display for Replacement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Replacement::Always => "always",
+            Replacement::Depth => "depth",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Counters of a transposition table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableStats {
+    pub capacity: usize,
+    pub policy: Replacement,
+    pub stores: usize,
+    /// Lookups that found the state
+    pub hits: usize,
+    /// Stores that dropped another state
+    pub evictions: usize,
+}
+
+impl std::fmt::Display for TableStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Transposition table: {} hits, {} stores, {} evictions ({} entries, {} replacement)",
+            self.hits, self.stores, self.evictions, self.capacity, self.policy
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    /// Lower bound of the distance to the goal
+    bound: u32,
+    /// Moves left to the cost bound when the bound was proven
+    work: u32,
+}
+
+/// Fixed size table of proven lower bounds, indexed by the Zobrist key of the states.
+///
+/// Only the keys are stored, so two states sharing a key would share a bound,
+/// which is negligible with 64-bit keys.
+pub(super) struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    stats: TableStats,
+}
+
+impl TranspositionTable {
+    pub(super) fn new(capacity: usize, policy: Replacement) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: vec![None; capacity],
+            stats: TableStats {
+                capacity,
+                policy,
+                stores: 0,
+                hits: 0,
+                evictions: 0,
+            },
+        }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    pub(super) fn get(&mut self, puzzle: &Puzzle) -> Option<usize> {
+        let key = puzzle.zobrist_key();
+        match self.entries[self.slot(key)] {
+            Some(entry) if entry.key == key => {
+                self.stats.hits += 1;
+                Some(entry.bound as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// Record that `puzzle` is at least `bound` moves from the goal,
+    /// found with `work` moves left to the cost bound
+    pub(super) fn store(&mut self, puzzle: &Puzzle, bound: usize, work: usize) {
+        let key = puzzle.zobrist_key();
+        let slot = self.slot(key);
+        let entry = Entry {
+            key,
+            bound: bound.min(u32::MAX as usize) as u32,
+            work: work.min(u32::MAX as usize) as u32,
+        };
+        match self.entries[slot] {
+            Some(old) if old.key == key => {
+                if entry.bound > old.bound {
+                    self.entries[slot] = Some(entry);
+                    self.stats.stores += 1;
+                }
+            }
+            Some(old) => {
+                if self.stats.policy == Replacement::Depth && old.work > entry.work {
+                    return;
+                }
+                self.entries[slot] = Some(entry);
+                self.stats.stores += 1;
+                self.stats.evictions += 1;
+            }
+            None => {
+                self.entries[slot] = Some(entry);
+                self.stats.stores += 1;
+            }
+        }
+    }
+
+    pub(super) fn stats(&self) -> TableStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn test_transposition_table() -> anyhow::Result<()> {
+        let first = Puzzle::new_answer(3);
+        let mut second = first.clone();
+        second.move_blank(Move::Up)?;
+        let mut table = TranspositionTable::new(1, Replacement::Depth);
+        assert_eq!(table.get(&first), None);
+        table.store(&first, 3, 10);
+        table.store(&first, 2, 10);
+        assert_eq!(table.get(&first), Some(3));
+        // A shallower search does not evict a deeper one
+        table.store(&second, 5, 4);
+        assert_eq!(table.get(&second), None);
+        assert_eq!(table.get(&first), Some(3));
+        let mut table = TranspositionTable::new(1, Replacement::Always);
+        table.store(&first, 3, 10);
+        table.store(&second, 5, 4);
+        assert_eq!(table.get(&first), None);
+        assert_eq!(table.get(&second), Some(5));
+        let stats = table.stats();
+        assert_eq!((stats.stores, stats.hits, stats.evictions), (2, 1, 1));
+        Ok(())
+    }
+}
@@ -1,13 +1,27 @@
-use super::{astar, Output};
-use crate::Puzzle;
+use super::{astar, OpenOptions, Output, Session};
+use crate::{Goal, Puzzle};
 use anyhow::Result;
 
-fn uniform_cost(_puzzle: &Puzzle) -> usize {
+pub(super) fn uniform_cost(_puzzle: &Puzzle, _goal: &Goal) -> usize {
     0
 }
 
-pub(super) fn solve(puzzle: &Puzzle, timeout: Option<u64>) -> Result<Output> {
-    astar(puzzle.clone(), uniform_cost, false, timeout)
+pub(super) fn solve(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    timeout: Option<u64>,
+    options: OpenOptions,
+    session: Session,
+) -> Result<Output> {
+    astar(
+        puzzle.clone(),
+        goal,
+        uniform_cost,
+        false,
+        timeout,
+        options,
+        session,
+    )
 }
 
 #[cfg(test)]
@@ -17,7 +31,16 @@ mod tests {
     #[test]
     fn test_uniform_cost() {
         let mut puzzle = Puzzle::generate_solvable(3).unwrap();
-        let output = astar(puzzle.clone(), uniform_cost, false, None).unwrap();
+        let output = astar(
+            puzzle.clone(),
+            &Goal::snail(3),
+            uniform_cost,
+            false,
+            None,
+            OpenOptions::default(),
+            Session::default(),
+        )
+        .unwrap();
         for m in output.path {
             puzzle.move_blank(m).unwrap();
         }
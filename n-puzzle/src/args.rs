@@ -1,16 +1,71 @@
 use anyhow::{anyhow, Result};
+use rand::Rng;
 
 use std::env;
 
-use super::{Algorithm, Heuristic, PuzzleSettings, MAX_PUZZLE_SIZE};
+use super::{
+    Algorithm, Goal, GoalSettings, Heuristic, OpenList, OpenOptions, PuzzleSettings, Replacement,
+    TieBreak, Topology, MAX_PUZZLE_SIZE,
+};
+use crate::algorithm::{MAX_THREADS, MAX_WINDOW};
+use crate::explore::MAX_CELLS as MAX_EXPLORE_CELLS;
+use crate::korf::KORF_100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Solve,
+    Batch,
+    Bench,
+    Korf,
+    Check,
+    Oracle,
+    Hint,
+    Explore,
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Settings {
+    pub command: Command,
     pub puzzle_settings: PuzzleSettings,
     pub algorithm: Option<Algorithm>,
     pub heuristic: Heuristic,
     pub timeout: Option<u64>,
     pub verbose: bool,
+    pub svg_path: Option<String>,
+    pub html_path: Option<String>,
+    pub inputs: Vec<String>,
+    pub jobs: Option<usize>,
+    pub algorithms: Vec<Algorithm>,
+    pub heuristics: Vec<Heuristic>,
+    pub count: Option<usize>,
+    pub seed: Option<u64>,
+    pub csv_path: Option<String>,
+    pub goal: Option<GoalSettings>,
+    /// Goal read from the target file, possibly with wildcards
+    pub target: Option<Goal>,
+    pub instances: Vec<usize>,
+    pub depth: Option<usize>,
+    pub cache_path: Option<String>,
+    pub interactive: bool,
+    pub solutions: Option<usize>,
+    pub count_solutions: bool,
+    pub optimize: bool,
+    pub window: Option<usize>,
+    pub core_size: Option<usize>,
+    pub threads: Option<usize>,
+    pub table_size: Option<usize>,
+    pub table_policy: Option<Replacement>,
+    pub node_budget: Option<usize>,
+    pub beam_width: Option<usize>,
+    pub tie_break: Option<TieBreak>,
+    pub open_list: Option<OpenList>,
+    pub board: Option<(usize, usize)>,
+    pub work_dir: Option<String>,
+    pub chunk: Option<usize>,
+    pub checkpoint_path: Option<String>,
+    pub checkpoint_interval: Option<u64>,
+    pub resume_path: Option<String>,
+    pub topology: Option<Topology>,
 }
 
 impl Settings {
@@ -22,11 +77,46 @@ impl Settings {
         verbose: bool,
     ) -> Self {
         Self {
+            command: Command::Solve,
             puzzle_settings,
             algorithm,
             heuristic,
             timeout,
             verbose,
+            svg_path: None,
+            html_path: None,
+            inputs: Vec::new(),
+            jobs: None,
+            algorithms: Vec::new(),
+            heuristics: Vec::new(),
+            count: None,
+            seed: None,
+            csv_path: None,
+            goal: None,
+            target: None,
+            instances: Vec::new(),
+            depth: None,
+            cache_path: None,
+            interactive: false,
+            solutions: None,
+            count_solutions: false,
+            optimize: false,
+            window: None,
+            core_size: None,
+            threads: None,
+            table_size: None,
+            table_policy: None,
+            node_budget: None,
+            beam_width: None,
+            tie_break: None,
+            open_list: None,
+            board: None,
+            work_dir: None,
+            chunk: None,
+            checkpoint_path: None,
+            checkpoint_interval: None,
+            resume_path: None,
+            topology: None,
         }
     }
 
@@ -39,17 +129,7 @@ impl Settings {
             None => {}
             Some(_) => return Err(anyhow!("Duplicate algorithm defined.")),
         }
-        match algorithm {
-            "astar" => self.algorithm = Some(Algorithm::AStar),
-            "uniform_cost" => self.algorithm = Some(Algorithm::UniformCost),
-            "greedy" => self.algorithm = Some(Algorithm::Greedy),
-            _ => {
-                return Err(anyhow!(
-                    "Not a valid algorithm: {}. Use astar, uniform_cost, or greedy",
-                    algorithm
-                ))
-            }
-        }
+        self.algorithm = Some(parse_algorithm(algorithm)?);
         Ok(())
     }
 
@@ -58,17 +138,30 @@ impl Settings {
             Heuristic::None => {}
             _ => return Err(anyhow!("Duplicate heuristic defined.")),
         }
-        match heuristic {
-            "manhattan" => self.heuristic = Heuristic::Manhattan,
-            "hamming" => self.heuristic = Heuristic::Hamming,
-            "linear_conflict" => self.heuristic = Heuristic::LinearConflict,
-            "inversion_distance" => self.heuristic = Heuristic::InversionDistance,
-            _ => {
-                return Err(anyhow!(
-                    "Not a valid heuristic: {}. Use manhattan, hamming, linear_conflict or inversion_distance",
-                    heuristic
-                ))
+        self.heuristic = parse_heuristic(heuristic)?;
+        Ok(())
+    }
+
+    /// Add comma separated algorithms to compare
+    pub fn add_algorithms(&mut self, algorithms: &str) -> Result<()> {
+        for algorithm in algorithms.split(',') {
+            let algorithm = parse_algorithm(algorithm)?;
+            if self.algorithms.contains(&algorithm) {
+                return Err(anyhow!("Duplicate algorithm defined."));
+            }
+            self.algorithms.push(algorithm);
+        }
+        Ok(())
+    }
+
+    /// Add comma separated heuristics to compare
+    pub fn add_heuristics(&mut self, heuristics: &str) -> Result<()> {
+        for heuristic in heuristics.split(',') {
+            let heuristic = parse_heuristic(heuristic)?;
+            if self.heuristics.contains(&heuristic) {
+                return Err(anyhow!("Duplicate heuristic defined."));
             }
+            self.heuristics.push(heuristic);
         }
         Ok(())
     }
@@ -112,412 +205,2333 @@ impl Settings {
         Ok(())
     }
 
-    pub fn set_size(&mut self, size: &str) -> Result<()> {
-        match self.puzzle_settings {
-            PuzzleSettings::Size(0) => {}
-            _ => return Err(anyhow!("Duplicate size or text_path defined.")),
+    pub fn set_svg_path(&mut self, svg_path: &str) -> Result<()> {
+        if self.svg_path.is_some() {
+            return Err(anyhow!("Duplicate svg path defined."));
         }
-        let size: usize = match size.trim().parse() {
-            Ok(num) if num > 1 && num < MAX_PUZZLE_SIZE => num,
+        if !svg_path.ends_with(".svg") {
+            return Err(anyhow!(
+                "Not a valid file format: {}. File must be in .svg format",
+                svg_path
+            ));
+        }
+        self.svg_path = Some(svg_path.to_string());
+        Ok(())
+    }
+
+    pub fn set_html_path(&mut self, html_path: &str) -> Result<()> {
+        if self.html_path.is_some() {
+            return Err(anyhow!("Duplicate html path defined."));
+        }
+        if !html_path.ends_with(".html") {
+            return Err(anyhow!(
+                "Not a valid file format: {}. File must be in .html format",
+                html_path
+            ));
+        }
+        self.html_path = Some(html_path.to_string());
+        Ok(())
+    }
+
+    pub fn add_input(&mut self, input: &str) {
+        self.inputs.push(input.to_string());
+    }
+
+    pub fn set_jobs(&mut self, jobs: &str) -> Result<()> {
+        match self.jobs {
+            None => {}
+            Some(_) => return Err(anyhow!("Duplicate jobs defined.")),
+        }
+        let jobs: usize = match jobs.trim().parse() {
+            Ok(num) if num > 0 => num,
             Ok(_) => {
                 return Err(anyhow!(
-                    "Not a valid size: {}. Must be between 2 and {}.",
-                    size,
-                    MAX_PUZZLE_SIZE
+                    "Not a valid number of jobs: {}. Jobs must be more than 0",
+                    jobs
                 ))
             }
             Err(_) => {
                 return Err(anyhow!(
                     "Not a valid number: {}. Use numerical numbers",
-                    size
+                    jobs
                 ))
             }
         };
-        self.puzzle_settings = PuzzleSettings::Size(size);
+        self.jobs = Some(jobs);
         Ok(())
     }
 
-    pub fn apply_default_setting(&mut self) -> Result<()> {
-        if let PuzzleSettings::Size(0) = self.puzzle_settings {
-            return Err(anyhow!("Need size or text_path."));
+    pub fn set_count(&mut self, count: &str) -> Result<()> {
+        if self.count.is_some() {
+            return Err(anyhow!("Duplicate count defined."));
         }
-        if self.algorithm.is_none() {
-            self.algorithm = Some(Algorithm::AStar);
+        self.count = Some(parse_positive_number(count, "count")?);
+        Ok(())
+    }
+
+    pub fn set_seed(&mut self, seed: &str) -> Result<()> {
+        if self.seed.is_some() {
+            return Err(anyhow!("Duplicate seed defined."));
         }
-        let is_huristic = self.algorithm.unwrap().is_heuristic();
-        match self.heuristic {
-            Heuristic::None => {
-                if is_huristic {
-                    self.heuristic = Heuristic::Manhattan;
-                }
+        match seed.trim().parse() {
+            Ok(seed) => self.seed = Some(seed),
+            Err(_) => {
+                return Err(anyhow!(
+                    "Not a valid number: {}. Use numerical numbers",
+                    seed
+                ))
             }
+        }
+        Ok(())
+    }
+
+    pub fn set_csv_path(&mut self, csv_path: &str) -> Result<()> {
+        if self.csv_path.is_some() {
+            return Err(anyhow!("Duplicate csv path defined."));
+        }
+        if !csv_path.ends_with(".csv") {
+            return Err(anyhow!(
+                "Not a valid file format: {}. File must be in .csv format",
+                csv_path
+            ));
+        }
+        self.csv_path = Some(csv_path.to_string());
+        Ok(())
+    }
+
+    pub fn set_goal(&mut self, goal: &str) -> Result<()> {
+        if self.goal.is_some() {
+            return Err(anyhow!("Duplicate goal defined."));
+        }
+        self.goal = match goal {
+            "snail" => Some(GoalSettings::Snail),
+            "row_major" => Some(GoalSettings::RowMajor),
             _ => {
-                if !is_huristic {
-                    return Err(anyhow!(
-                        "Heuristic specified for algorithm that doesn't need heuristic."
-                    ));
-                }
+                return Err(anyhow!(
+                    "Not a valid goal: {}. Use snail or row_major",
+                    goal
+                ))
             }
-        }
+        };
         Ok(())
     }
-}
 
-// Get arguments
-pub fn get_args() -> Vec<String> {
-    env::args().collect()
-}
+    pub fn set_target(&mut self, target_path: &str) -> Result<()> {
+        if self.target.is_some() {
+            return Err(anyhow!("Duplicate target defined."));
+        }
+        if !target_path.ends_with(".txt") {
+            return Err(anyhow!(
+                "Not a valid file format: {}. File must be in .txt format",
+                target_path
+            ));
+        }
+        self.target = Some(Goal::parse_text(target_path)?);
+        Ok(())
+    }
 
-// Parse arguments
-// Expected format: executable (file | size) [-a algorithm] [-h heuristic] [-t timeout] [--verbose]
-pub fn parse_args(args: Vec<String>) -> Result<Option<Settings>> {
-    let len_args: usize = args.len();
-    let mut settings: Settings = Settings::new_default();
+    /// Open set of the astar, uniform_cost and greedy searches
+    pub fn open_options(&self) -> OpenOptions {
+        OpenOptions {
+            list: self.open_list.unwrap_or_default(),
+            tie_break: self.tie_break,
+        }
+    }
 
-    if len_args == 1 {
-        println!(
-            "usage: {} (file | size) [-a algorithm] [-h heuristic] [-t timeout] [--verbose]",
-            args[0]
-        );
-        return Ok(None);
+    /// Goal of the given size: the board of the target file, possibly with wildcards, when one is defined,
+    /// otherwise the snail goal unless another one is defined
+    pub fn get_goal(&self, size: usize) -> Result<Goal> {
+        let goal = match &self.target {
+            Some(goal) => goal,
+            None => return Ok(Goal::new(self.goal.unwrap_or(GoalSettings::Snail), size)),
+        };
+        if goal.get_size() != size {
+            return Err(anyhow!(
+                "Target size {} does not match puzzle size {}.",
+                goal.get_size(),
+                size
+            ));
+        }
+        Ok(goal.clone())
     }
 
-    let mut i = 1;
-    while i < len_args {
-        let arg = args[i].as_str();
-        match arg {
-            "-a" | "--algorithm" => {
-                i += 1;
-                if i == len_args {
-                    return Err(anyhow!(
-                        "Need an algorithm: Use astar, uniform_cost, or greedy"
-                    ));
-                }
-                settings.set_algorithm(args[i].as_str())?
-            }
-            "-h" | "--heuristic" => {
-                i += 1;
-                if i == len_args {
-                    return Err(anyhow!(
-                        "Need a heuristic: Use manhattan, hamming, linear_conflict or inversion_distance"
-                    ));
-                }
-                settings.set_heuristic(args[i].as_str())?
+    /// Add comma separated instance numbers or ranges, e.g. `1,3,5-7`
+    pub fn add_instances(&mut self, instances: &str) -> Result<()> {
+        for instance in instances.split(',') {
+            let (first, last) = match instance.split_once('-') {
+                Some((first, last)) => (first, last),
+                None => (instance, instance),
+            };
+            let first = parse_positive_number(first, "instance")?;
+            let last = parse_positive_number(last, "instance")?;
+            if first > last || last > KORF_100.len() {
+                return Err(anyhow!(
+                    "Not a valid instance: {}. Must be between 1 and {}",
+                    instance,
+                    KORF_100.len()
+                ));
             }
-            "-t" | "--timeout" => {
-                i += 1;
-                if i == len_args {
-                    return Err(anyhow!("Need a time: Use numerical numbers"));
+            for number in first..=last {
+                if self.instances.contains(&number) {
+                    return Err(anyhow!("Duplicate instance defined: {}.", number));
                 }
-                settings.set_timeout(args[i].as_str())?
+                self.instances.push(number);
             }
-            "--verbose" => settings.verbose = true,
-            _ => match arg.trim().parse::<usize>() {
-                Ok(_) => settings.set_size(arg)?,
-                Err(_) => settings.set_text_path(arg)?,
-            },
         }
-        i += 1;
+        Ok(())
     }
 
-    settings.apply_default_setting()?;
-    Ok(Some(settings))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn set_cache_path(&mut self, cache_path: &str) -> Result<()> {
+        if self.cache_path.is_some() {
+            return Err(anyhow!("Duplicate cache path defined."));
+        }
+        self.cache_path = Some(cache_path.to_string());
+        Ok(())
+    }
 
-    #[test]
-    fn test_size_normal() -> Result<()> {
-        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "2".into()];
-        let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(2),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+    pub fn set_depth(&mut self, depth: &str) -> Result<()> {
+        if self.depth.is_some() {
+            return Err(anyhow!("Duplicate depth defined."));
+        }
+        self.depth = Some(parse_positive_number(depth, "depth")?);
         Ok(())
     }
 
-    #[test]
-    fn test_size_one() {
-        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "1".into()];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+    /// Board of the explore command, given as `WxH`
+    pub fn set_board(&mut self, board: &str) -> Result<()> {
+        if self.board.is_some() || !matches!(self.puzzle_settings, PuzzleSettings::Size(0)) {
+            return Err(anyhow!("Duplicate size or board defined."));
+        }
+        let (width, height) = board
+            .split_once('x')
+            .ok_or_else(|| anyhow!("Not a valid board: {}. Use WxH, e.g. 3x5", board))?;
+        self.board = Some((
+            parse_positive_number(width, "board width")?,
+            parse_positive_number(height, "board height")?,
+        ));
+        Ok(())
     }
 
-    #[test]
-    fn test_size_zero() {
-        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "0".into()];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+    pub fn set_work_dir(&mut self, work_dir: &str) -> Result<()> {
+        if self.work_dir.is_some() {
+            return Err(anyhow!("Duplicate dir defined."));
+        }
+        self.work_dir = Some(work_dir.to_string());
+        Ok(())
     }
 
-    #[test]
-    fn test_text_path_valid() -> Result<()> {
-        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "test.txt".into()];
-        let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::TextPath("test.txt".into()),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+    pub fn set_chunk(&mut self, chunk: &str) -> Result<()> {
+        if self.chunk.is_some() {
+            return Err(anyhow!("Duplicate chunk defined."));
+        }
+        self.chunk = Some(parse_positive_number(chunk, "chunk")?);
         Ok(())
     }
 
-    #[test]
-    fn test_text_path_invalid() {
-        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "test.py".into()];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+    pub fn set_checkpoint_path(&mut self, checkpoint_path: &str) -> Result<()> {
+        if self.checkpoint_path.is_some() {
+            return Err(anyhow!("Duplicate checkpoint defined."));
+        }
+        self.checkpoint_path = Some(checkpoint_path.to_string());
+        Ok(())
     }
 
-    #[test]
-    fn test_algorithm_astar() -> Result<()> {
-        let args: Vec<String> = vec![
-            "target/debug/n-puzzle".into(),
-            "3".into(),
-            "-a".into(),
-            "astar".into(),
-        ];
-        let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+    pub fn set_checkpoint_interval(&mut self, interval: &str) -> Result<()> {
+        if self.checkpoint_interval.is_some() {
+            return Err(anyhow!("Duplicate checkpoint interval defined."));
+        }
+        self.checkpoint_interval =
+            Some(parse_positive_number(interval, "checkpoint interval")? as u64);
         Ok(())
     }
 
-    #[test]
-    fn test_algorithm_uniform_cost() -> Result<()> {
-        let args: Vec<String> = vec![
-            "target/debug/n-puzzle".into(),
-            "3".into(),
-            "-a".into(),
-            "uniform_cost".into(),
-        ];
-        let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::UniformCost),
-            Heuristic::None,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+    pub fn set_resume_path(&mut self, resume_path: &str) -> Result<()> {
+        if self.resume_path.is_some() {
+            return Err(anyhow!("Duplicate resume defined."));
+        }
+        self.resume_path = Some(resume_path.to_string());
         Ok(())
     }
 
-    #[test]
-    fn test_algorithm_greedy() -> Result<()> {
-        let args: Vec<String> = vec![
-            "target/debug/n-puzzle".into(),
-            "3".into(),
-            "-a".into(),
-            "greedy".into(),
-        ];
-        let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::Greedy),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+    pub fn set_topology(&mut self, topology: &str) -> Result<()> {
+        if self.topology.is_some() {
+            return Err(anyhow!("Duplicate topology defined."));
+        }
+        self.topology = Some(match topology {
+            "plane" => Topology::Plane,
+            "torus" => Topology::Torus,
+            _ => {
+                return Err(anyhow!(
+                    "Not a valid topology: {}. Use plane or torus",
+                    topology
+                ))
+            }
+        });
         Ok(())
     }
 
+    pub fn set_solutions(&mut self, solutions: &str) -> Result<()> {
+        if self.solutions.is_some() {
+            return Err(anyhow!("Duplicate solutions defined."));
+        }
+        self.solutions = Some(parse_positive_number(solutions, "number of solutions")?);
+        Ok(())
+    }
+
+    pub fn set_window(&mut self, window: &str) -> Result<()> {
+        if self.window.is_some() {
+            return Err(anyhow!("Duplicate window defined."));
+        }
+        self.window = Some(parse_bounded_number(window, "window", MAX_WINDOW)?);
+        Ok(())
+    }
+
+    pub fn set_threads(&mut self, threads: &str) -> Result<()> {
+        if self.threads.is_some() {
+            return Err(anyhow!("Duplicate threads defined."));
+        }
+        self.threads = Some(parse_bounded_number(
+            threads,
+            "number of threads",
+            MAX_THREADS,
+        )?);
+        Ok(())
+    }
+
+    pub fn set_table_size(&mut self, table_size: &str) -> Result<()> {
+        if self.table_size.is_some() {
+            return Err(anyhow!("Duplicate table size defined."));
+        }
+        self.table_size = Some(parse_positive_number(table_size, "table size")?);
+        Ok(())
+    }
+
+    pub fn set_table_policy(&mut self, table_policy: &str) -> Result<()> {
+        if self.table_policy.is_some() {
+            return Err(anyhow!("Duplicate table policy defined."));
+        }
+        self.table_policy = Some(match table_policy {
+            "always" => Replacement::Always,
+            "depth" => Replacement::Depth,
+            _ => {
+                return Err(anyhow!(
+                    "Not a valid table policy: {}. Use always or depth",
+                    table_policy
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    pub fn set_node_budget(&mut self, node_budget: &str) -> Result<()> {
+        if self.node_budget.is_some() {
+            return Err(anyhow!("Duplicate node budget defined."));
+        }
+        self.node_budget = Some(parse_positive_number(node_budget, "node budget")?);
+        Ok(())
+    }
+
+    pub fn set_beam_width(&mut self, beam_width: &str) -> Result<()> {
+        if self.beam_width.is_some() {
+            return Err(anyhow!("Duplicate beam width defined."));
+        }
+        self.beam_width = Some(parse_positive_number(beam_width, "beam width")?);
+        Ok(())
+    }
+
+    pub fn set_tie_break(&mut self, tie_break: &str) -> Result<()> {
+        if self.tie_break.is_some() {
+            return Err(anyhow!("Duplicate tie-break defined."));
+        }
+        self.tie_break = Some(match tie_break {
+            "higher_g" => TieBreak::HigherG,
+            "lower_h" => TieBreak::LowerH,
+            "lifo" => TieBreak::Lifo,
+            "fifo" => TieBreak::Fifo,
+            // The seed is drawn once every option is known
+            "random" => TieBreak::Random(0),
+            _ => {
+                return Err(anyhow!(
+                    "Not a valid tie-break: {}. Use higher_g, lower_h, lifo, fifo or random",
+                    tie_break
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    pub fn set_open_list(&mut self, open_list: &str) -> Result<()> {
+        if self.open_list.is_some() {
+            return Err(anyhow!("Duplicate open list defined."));
+        }
+        self.open_list = Some(match open_list {
+            "heap" => OpenList::Heap,
+            "buckets" => OpenList::Buckets,
+            _ => {
+                return Err(anyhow!(
+                    "Not a valid open list: {}. Use heap or buckets",
+                    open_list
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    pub fn set_core_size(&mut self, core_size: &str) -> Result<()> {
+        if self.core_size.is_some() {
+            return Err(anyhow!("Duplicate core defined."));
+        }
+        self.core_size = match core_size.trim().parse() {
+            Ok(num) if (2..=4).contains(&num) => Some(num),
+            Ok(_) => {
+                return Err(anyhow!(
+                    "Not a valid core: {}. Must be between 2 and 4.",
+                    core_size
+                ))
+            }
+            Err(_) => {
+                return Err(anyhow!(
+                    "Not a valid number: {}. Use numerical numbers",
+                    core_size
+                ))
+            }
+        };
+        Ok(())
+    }
+
+    pub fn set_size(&mut self, size: &str) -> Result<()> {
+        match self.puzzle_settings {
+            PuzzleSettings::Size(0) => {}
+            _ => return Err(anyhow!("Duplicate size or text_path defined.")),
+        }
+        let size: usize = match size.trim().parse() {
+            Ok(num) if num > 1 && num <= MAX_PUZZLE_SIZE => num,
+            Ok(_) => {
+                return Err(anyhow!(
+                    "Not a valid size: {}. Must be between 2 and {}.",
+                    size,
+                    MAX_PUZZLE_SIZE
+                ))
+            }
+            Err(_) => {
+                return Err(anyhow!(
+                    "Not a valid number: {}. Use numerical numbers",
+                    size
+                ))
+            }
+        };
+        self.puzzle_settings = PuzzleSettings::Size(size);
+        Ok(())
+    }
+
+    pub fn apply_default_setting(&mut self) -> Result<()> {
+        let random_tie_break = matches!(self.tie_break, Some(TieBreak::Random(_)));
+        if !matches!(self.command, Command::Bench | Command::Check)
+            && (self.count.is_some() || (self.seed.is_some() && !random_tie_break))
+        {
+            return Err(anyhow!(
+                "Count and seed can only be used with the bench and check commands, or seed with the random tie-break."
+            ));
+        }
+        if self.command != Command::Bench && self.csv_path.is_some() {
+            return Err(anyhow!("Csv can only be used with the bench command."));
+        }
+        if self.cache_path.is_some()
+            && !matches!(self.command, Command::Oracle | Command::Hint)
+            && self.heuristic != Heuristic::Exact
+        {
+            return Err(anyhow!(
+                "Cache can only be used with the oracle and hint commands or the exact heuristic."
+            ));
+        }
+        if !matches!(self.command, Command::Check | Command::Explore) && self.depth.is_some() {
+            return Err(anyhow!(
+                "Depth can only be used with the check and explore commands."
+            ));
+        }
+        if self.command != Command::Explore && (self.work_dir.is_some() || self.chunk.is_some()) {
+            return Err(anyhow!(
+                "Dir and chunk can only be used with the explore command."
+            ));
+        }
+        if self.command != Command::Korf && !self.instances.is_empty() {
+            return Err(anyhow!("Instances can only be used with the korf command."));
+        }
+        if self.goal.is_some() && self.target.is_some() {
+            return Err(anyhow!("Goal and target cannot be used together."));
+        }
+        if self.command != Command::Solve && (self.solutions.is_some() || self.count_solutions) {
+            return Err(anyhow!(
+                "Solutions and count-solutions can only be used to solve a single puzzle."
+            ));
+        }
+        if self.command != Command::Solve && self.optimize {
+            return Err(anyhow!(
+                "Optimize can only be used to solve a single puzzle."
+            ));
+        }
+        if !self.optimize && self.window.is_some() {
+            return Err(anyhow!("Window can only be used with optimize."));
+        }
+        if self.core_size.is_some()
+            && (self.command != Command::Solve || self.algorithm != Some(Algorithm::Hierarchical))
+        {
+            return Err(anyhow!(
+                "Core can only be used to solve a single puzzle with the hierarchical algorithm."
+            ));
+        }
+        if self.threads.is_some()
+            && (self.command != Command::Solve
+                || !matches!(
+                    self.algorithm,
+                    Some(Algorithm::IdaStar | Algorithm::Hierarchical)
+                ))
+        {
+            return Err(anyhow!(
+                "Threads can only be used to solve a single puzzle with the ida_star or hierarchical algorithm."
+            ));
+        }
+        if self.table_size.is_some()
+            && (self.command != Command::Solve
+                || !matches!(
+                    self.algorithm,
+                    Some(Algorithm::IdaStar | Algorithm::Hierarchical)
+                ))
+        {
+            return Err(anyhow!(
+                "Tt-size can only be used to solve a single puzzle with the ida_star or hierarchical algorithm."
+            ));
+        }
+        if self.table_size.is_some() && self.threads.is_some_and(|threads| threads > 1) {
+            return Err(anyhow!("Tt-size cannot be used with more than one thread."));
+        }
+        if self.node_budget.is_some()
+            && (self.command != Command::Solve || self.algorithm != Some(Algorithm::SmaStar))
+        {
+            return Err(anyhow!(
+                "Node-budget can only be used to solve a single puzzle with the sma_star algorithm."
+            ));
+        }
+        if self.beam_width.is_some()
+            && (self.command != Command::Solve || self.algorithm != Some(Algorithm::Beam))
+        {
+            return Err(anyhow!(
+                "Beam-width can only be used to solve a single puzzle with the beam algorithm."
+            ));
+        }
+        if (self.tie_break.is_some() || self.open_list.is_some())
+            && (!matches!(
+                self.command,
+                Command::Solve | Command::Batch | Command::Bench | Command::Korf
+            ) || !matches!(
+                self.algorithm,
+                None | Some(Algorithm::AStar | Algorithm::UniformCost | Algorithm::Greedy)
+            ))
+        {
+            return Err(anyhow!(
+                "Tie-break and open-list can only be used with the solve, batch, bench and korf commands and the astar, uniform_cost or greedy algorithm."
+            ));
+        }
+        if self.open_list == Some(OpenList::Buckets)
+            && !matches!(
+                self.tie_break,
+                None | Some(TieBreak::HigherG | TieBreak::LowerH)
+            )
+        {
+            return Err(anyhow!(
+                "Open-list buckets always expand the state with the most moves first. Use the higher_g or lower_h tie-break."
+            ));
+        }
+        if random_tie_break {
+            let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+            self.tie_break = Some(TieBreak::Random(seed));
+        }
+        if self.table_size.is_none() && self.table_policy.is_some() {
+            return Err(anyhow!("Tt-policy can only be used with tt-size."));
+        }
+        let checkpointing = self.checkpoint_path.is_some() || self.resume_path.is_some();
+        if checkpointing && self.command != Command::Solve {
+            return Err(anyhow!(
+                "Checkpoint and resume can only be used to solve a single puzzle."
+            ));
+        }
+        if self.checkpoint_path.is_some()
+            && !matches!(
+                self.algorithm,
+                None | Some(
+                    Algorithm::AStar
+                        | Algorithm::UniformCost
+                        | Algorithm::Greedy
+                        | Algorithm::IdaStar
+                )
+            )
+        {
+            return Err(anyhow!(
+                "Checkpoint can only be used with the astar, uniform_cost, greedy or ida_star algorithm."
+            ));
+        }
+        if checkpointing && self.threads.is_some_and(|threads| threads > 1) {
+            return Err(anyhow!(
+                "Checkpoint and resume cannot be used with more than one thread."
+            ));
+        }
+        if !checkpointing && self.checkpoint_interval.is_some() {
+            return Err(anyhow!(
+                "Checkpoint-interval can only be used with checkpoint or resume."
+            ));
+        }
+        if self.resume_path.is_some()
+            && (!matches!(self.puzzle_settings, PuzzleSettings::Size(0))
+                || self.algorithm.is_some()
+                || self.heuristic != Heuristic::None
+                || self.goal.is_some()
+                || self.target.is_some()
+                || self.tie_break.is_some()
+                || self.open_list.is_some()
+                || self.topology.is_some())
+        {
+            return Err(anyhow!(
+                "The checkpoint holds the puzzle, goal, topology, algorithm, heuristic and open list of the search it resumes."
+            ));
+        }
+        if self.topology.is_some()
+            && !matches!(
+                self.command,
+                Command::Solve | Command::Check | Command::Explore
+            )
+        {
+            return Err(anyhow!(
+                "Topology can only be used with the solve, check and explore commands."
+            ));
+        }
+        if self.topology == Some(Topology::Torus)
+            && matches!(
+                self.algorithm,
+                Some(Algorithm::Constructive | Algorithm::Hierarchical)
+            )
+        {
+            return Err(anyhow!(
+                "The constructive and hierarchical algorithms cannot be used on a torus board."
+            ));
+        }
+        if self.command != Command::Hint && self.interactive {
+            return Err(anyhow!(
+                "Interactive can only be used with the hint command."
+            ));
+        }
+        match self.command {
+            Command::Solve => {
+                if self.jobs.is_some() {
+                    return Err(anyhow!("Jobs can only be used with the batch command."));
+                }
+                if self.resume_path.is_some() {
+                    return Ok(());
+                }
+                if let PuzzleSettings::Size(0) = self.puzzle_settings {
+                    return Err(anyhow!("Need size or text_path."));
+                }
+            }
+            Command::Batch => {
+                if self.inputs.is_empty() {
+                    return Err(anyhow!("Need a file, directory or glob pattern."));
+                }
+                if self.svg_path.is_some() || self.html_path.is_some() {
+                    return Err(anyhow!("Export is not available with the batch command."));
+                }
+                if self.jobs.is_none() {
+                    self.jobs = Some(1);
+                }
+            }
+            Command::Bench => return self.apply_default_bench_setting(),
+            Command::Check => return self.apply_default_check_setting(),
+            Command::Oracle => return self.apply_default_oracle_setting(),
+            Command::Explore => return self.apply_default_explore_setting(),
+            Command::Korf => self.apply_default_korf_setting()?,
+            Command::Hint => self.apply_default_hint_setting()?,
+        }
+
+        if self.algorithm.is_none() {
+            self.algorithm = Some(Algorithm::AStar);
+        }
+        let is_huristic = self.algorithm.unwrap().is_heuristic();
+        match self.heuristic {
+            Heuristic::None => {
+                if is_huristic {
+                    self.heuristic = Heuristic::Manhattan;
+                }
+            }
+            _ => {
+                if !is_huristic {
+                    return Err(anyhow!(
+                        "Heuristic specified for algorithm that doesn't need heuristic."
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_default_korf_setting(&mut self) -> Result<()> {
+        if !matches!(self.puzzle_settings, PuzzleSettings::Size(0)) || !self.inputs.is_empty() {
+            return Err(anyhow!(
+                "The korf command uses its own puzzles. Use --instances to choose them."
+            ));
+        }
+        if self.jobs.is_some() || self.svg_path.is_some() || self.html_path.is_some() {
+            return Err(anyhow!(
+                "Jobs and export are not available with the korf command."
+            ));
+        }
+        if self.target.is_some() {
+            return Err(anyhow!(
+                "Target is not available with the korf command, whose instances use the row_major goal."
+            ));
+        }
+        match self.goal {
+            None | Some(GoalSettings::RowMajor) => self.goal = Some(GoalSettings::RowMajor),
+            Some(_) => return Err(anyhow!("The korf instances use the row_major goal.")),
+        }
+        if self.instances.is_empty() {
+            self.instances = (1..=KORF_100.len()).collect();
+        }
+        // An optimal search that fits in memory on the hard instances, unless an open list is asked for
+        if self.algorithm.is_none() && self.tie_break.is_none() && self.open_list.is_none() {
+            self.algorithm = Some(Algorithm::IdaStar);
+        }
+        if self.heuristic == Heuristic::None
+            && self.algorithm.unwrap_or(Algorithm::AStar).is_heuristic()
+        {
+            self.heuristic = Heuristic::InversionDistance;
+        }
+        Ok(())
+    }
+
+    fn apply_default_oracle_setting(&mut self) -> Result<()> {
+        if !matches!(self.puzzle_settings, PuzzleSettings::TextPath(_)) {
+            return Err(anyhow!("The oracle command needs a 3x3 puzzle file."));
+        }
+        if self.algorithm.is_some()
+            || self.heuristic != Heuristic::None
+            || self.timeout.is_some()
+            || self.jobs.is_some()
+            || self.svg_path.is_some()
+            || self.html_path.is_some()
+        {
+            return Err(anyhow!(
+                "Algorithm, heuristic, timeout, jobs and export are not available with the oracle command."
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_default_explore_setting(&mut self) -> Result<()> {
+        if let PuzzleSettings::Size(size) = self.puzzle_settings {
+            if size > 0 {
+                if self.board.is_some() {
+                    return Err(anyhow!("Duplicate size or board defined."));
+                }
+                self.board = Some((size, size));
+            }
+        }
+        let (width, height) = match (&self.puzzle_settings, self.board) {
+            (PuzzleSettings::Size(_), Some(board)) if self.inputs.is_empty() => board,
+            _ => {
+                return Err(anyhow!(
+                    "The explore command needs a size or a board, e.g. 3x5."
+                ))
+            }
+        };
+        if width < 2 || height < 2 || width * height > MAX_EXPLORE_CELLS {
+            return Err(anyhow!(
+                "Not a valid board: {}x{}. The explore command needs at least 2 rows and columns and at most {} cells",
+                width,
+                height,
+                MAX_EXPLORE_CELLS
+            ));
+        }
+        if self.work_dir.is_none() {
+            return Err(anyhow!(
+                "The explore command needs a working directory. Use --dir."
+            ));
+        }
+        if self.algorithm.is_some()
+            || self.heuristic != Heuristic::None
+            || self.timeout.is_some()
+            || self.jobs.is_some()
+            || self.svg_path.is_some()
+            || self.html_path.is_some()
+        {
+            return Err(anyhow!(
+                "Algorithm, heuristic, timeout, jobs and export are not available with the explore command."
+            ));
+        }
+        if width != height
+            && (self.target.is_some() || !matches!(self.goal, None | Some(GoalSettings::RowMajor)))
+        {
+            return Err(anyhow!(
+                "Boards that are not square can only start from the row_major goal."
+            ));
+        }
+        Ok(())
+    }
+
+    fn apply_default_hint_setting(&mut self) -> Result<()> {
+        if !matches!(self.puzzle_settings, PuzzleSettings::TextPath(_)) {
+            return Err(anyhow!("The hint command needs a puzzle file."));
+        }
+        if self.jobs.is_some() || self.svg_path.is_some() || self.html_path.is_some() {
+            return Err(anyhow!(
+                "Jobs and export are not available with the hint command."
+            ));
+        }
+        // An optimal search, so that the hints give every shortest move
+        if self.algorithm.is_none() {
+            self.algorithm = Some(Algorithm::IdaStar);
+        }
+        let partial = self
+            .target
+            .as_ref()
+            .is_some_and(|target| target.is_partial());
+        if self.heuristic == Heuristic::None && self.algorithm.unwrap().is_heuristic() && !partial {
+            self.heuristic = Heuristic::InversionDistance;
+        }
+        Ok(())
+    }
+
+    fn apply_default_check_setting(&mut self) -> Result<()> {
+        if let PuzzleSettings::TextPath(_) = self.puzzle_settings {
+            return Err(anyhow!("The check command needs a size, not a file."));
+        }
+        if !self.inputs.is_empty() {
+            return Err(anyhow!("The check command needs a size, not a file."));
+        }
+        if self.algorithm.is_some()
+            || self.timeout.is_some()
+            || self.jobs.is_some()
+            || self.svg_path.is_some()
+            || self.html_path.is_some()
+        {
+            return Err(anyhow!(
+                "Algorithm, timeout, jobs and export are not available with the check command."
+            ));
+        }
+        if let PuzzleSettings::Size(0) = self.puzzle_settings {
+            self.puzzle_settings = PuzzleSettings::Size(3);
+        }
+        if self.heuristics.contains(&Heuristic::Exact)
+            && !matches!(self.puzzle_settings, PuzzleSettings::Size(3))
+        {
+            return Err(anyhow!(
+                "The exact heuristic can only be checked on 3x3 boards."
+            ));
+        }
+        if self.heuristics.is_empty() {
+            self.heuristics = match self.topology {
+                // The other heuristics can overestimate the distance on a torus
+                Some(Topology::Torus) => vec![Heuristic::Manhattan, Heuristic::Hamming],
+                _ => vec![
+                    Heuristic::Manhattan,
+                    Heuristic::Hamming,
+                    Heuristic::LinearConflict,
+                    Heuristic::InversionDistance,
+                ],
+            };
+        }
+        Ok(())
+    }
+
+    fn apply_default_bench_setting(&mut self) -> Result<()> {
+        let has_size = !matches!(self.puzzle_settings, PuzzleSettings::Size(0));
+        if self.inputs.is_empty() && !has_size {
+            return Err(anyhow!("Need a size, file, directory or glob pattern."));
+        }
+        if !has_size && (self.count.is_some() || self.seed.is_some()) {
+            return Err(anyhow!("Count and seed need a size."));
+        }
+        if self.jobs.is_some() || self.svg_path.is_some() || self.html_path.is_some() {
+            return Err(anyhow!(
+                "Jobs and export are not available with the bench command."
+            ));
+        }
+        if has_size && self.count.is_none() {
+            self.count = Some(10);
+        }
+        if self.algorithms.is_empty() {
+            self.algorithms.push(Algorithm::AStar);
+        }
+        if self.heuristics.is_empty() {
+            self.heuristics = vec![
+                Heuristic::Manhattan,
+                Heuristic::Hamming,
+                Heuristic::LinearConflict,
+                Heuristic::InversionDistance,
+            ];
+        }
+        Ok(())
+    }
+}
+
+fn parse_algorithm(algorithm: &str) -> Result<Algorithm> {
+    match algorithm {
+        "astar" => Ok(Algorithm::AStar),
+        "uniform_cost" => Ok(Algorithm::UniformCost),
+        "greedy" => Ok(Algorithm::Greedy),
+        "constructive" => Ok(Algorithm::Constructive),
+        "ida_star" => Ok(Algorithm::IdaStar),
+        "hierarchical" => Ok(Algorithm::Hierarchical),
+        "rbfs" => Ok(Algorithm::Rbfs),
+        "sma_star" => Ok(Algorithm::SmaStar),
+        "beam" => Ok(Algorithm::Beam),
+        _ => Err(anyhow!(
+            "Not a valid algorithm: {}. Use astar, uniform_cost, greedy, constructive, ida_star, hierarchical, rbfs, sma_star or beam",
+            algorithm
+        )),
+    }
+}
+
+fn parse_heuristic(heuristic: &str) -> Result<Heuristic> {
+    match heuristic {
+        "manhattan" => Ok(Heuristic::Manhattan),
+        "hamming" => Ok(Heuristic::Hamming),
+        "linear_conflict" => Ok(Heuristic::LinearConflict),
+        "inversion_distance" => Ok(Heuristic::InversionDistance),
+        "exact" => Ok(Heuristic::Exact),
+        _ => Err(anyhow!(
+            "Not a valid heuristic: {}. Use manhattan, hamming, linear_conflict, inversion_distance or exact",
+            heuristic
+        )),
+    }
+}
+
+fn parse_positive_number(value: &str, name: &str) -> Result<usize> {
+    match value.trim().parse() {
+        Ok(num) if num > 0 => Ok(num),
+        Ok(_) => Err(anyhow!(
+            "Not a valid {}: {}. Must be more than 0",
+            name,
+            value
+        )),
+        Err(_) => Err(anyhow!(
+            "Not a valid number: {}. Use numerical numbers",
+            value
+        )),
+    }
+}
+
+fn parse_bounded_number(value: &str, name: &str, max: usize) -> Result<usize> {
+    match parse_positive_number(value, name)? {
+        num if num <= max => Ok(num),
+        _ => Err(anyhow!(
+            "Not a valid {}: {}. Must be between 1 and {}",
+            name,
+            value,
+            max
+        )),
+    }
+}
+
+// Get arguments
+pub fn get_args() -> Vec<String> {
+    env::args().collect()
+}
+
+// Parse arguments
+// Expected format: executable (file | size | --resume checkpoint) [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--solutions cap] [--count-solutions] [--optimize [--window moves]] [--core size] [--threads n] [--tt-size entries [--tt-policy policy]] [--node-budget nodes] [--beam-width k] [--tie-break policy [--seed seed]] [--open-list list] [--topology topology] [--checkpoint file] [--checkpoint-interval seconds] [--svg file] [--html file] [--verbose]
+//               or executable batch (file | directory | glob)... [-j jobs] [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--tie-break policy] [--open-list list]
+//               or executable bench (size | file | directory | glob)... [-n count] [--seed seed] [-a algorithms] [-h heuristics] [-t timeout] [--goal goal | --target file] [--tie-break policy] [--open-list list] [--csv file]
+//               or executable korf [--instances list] [-a algorithm] [-h heuristic] [-t timeout] [--tie-break policy] [--open-list list]
+//               or executable check [size] [-h heuristics] [-n count] [--seed seed] [--depth depth] [--goal goal | --target file] [--topology topology]
+//               or executable oracle file [--goal goal | --target file] [--cache file]
+//               or executable hint file [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--cache file] [--interactive]
+//               or executable explore (size | WxH) --dir directory [--depth depth] [--chunk states] [--goal goal | --target file] [--topology topology]
+pub fn parse_args(args: Vec<String>) -> Result<Option<Settings>> {
+    let len_args: usize = args.len();
+    let mut settings: Settings = Settings::new_default();
+
+    if len_args == 1 {
+        println!(
+            "usage: {} (file | size | --resume checkpoint) [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--solutions cap] [--count-solutions] [--optimize [--window moves]] [--core size] [--threads n] [--tt-size entries [--tt-policy policy]] [--node-budget nodes] [--beam-width k] [--tie-break policy [--seed seed]] [--open-list list] [--topology topology] [--checkpoint file] [--checkpoint-interval seconds] [--svg file] [--html file] [--verbose]",
+            args[0]
+        );
+        println!(
+            "       {} batch (file | directory | glob)... [-j jobs] [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--tie-break policy] [--open-list list]",
+            args[0]
+        );
+        println!(
+            "       {} bench (size | file | directory | glob)... [-n count] [--seed seed] [-a algorithms] [-h heuristics] [-t timeout] [--goal goal | --target file] [--tie-break policy] [--open-list list] [--csv file]",
+            args[0]
+        );
+        println!(
+            "       {} korf [--instances list] [-a algorithm] [-h heuristic] [-t timeout] [--tie-break policy] [--open-list list]",
+            args[0]
+        );
+        println!(
+            "       {} check [size] [-h heuristics] [-n count] [--seed seed] [--depth depth] [--goal goal | --target file] [--topology topology]",
+            args[0]
+        );
+        println!(
+            "       {} oracle file [--goal goal | --target file] [--cache file]",
+            args[0]
+        );
+        println!(
+            "       {} hint file [-a algorithm] [-h heuristic] [-t timeout] [--goal goal | --target file] [--cache file] [--interactive]",
+            args[0]
+        );
+        println!(
+            "       {} explore (size | WxH) --dir directory [--depth depth] [--chunk states] [--goal goal | --target file] [--topology topology]",
+            args[0]
+        );
+        return Ok(None);
+    }
+
+    let mut i = 1;
+    match args[1].as_str() {
+        "batch" => settings.command = Command::Batch,
+        "bench" => settings.command = Command::Bench,
+        "korf" => settings.command = Command::Korf,
+        "check" => settings.command = Command::Check,
+        "oracle" => settings.command = Command::Oracle,
+        "hint" => settings.command = Command::Hint,
+        "explore" => settings.command = Command::Explore,
+        _ => i -= 1,
+    }
+    i += 1;
+    while i < len_args {
+        let arg = args[i].as_str();
+        match arg {
+            "-a" | "--algorithm" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!(
+                        "Need an algorithm: Use astar, uniform_cost, greedy, constructive, ida_star, hierarchical, rbfs, sma_star or beam"
+                    ));
+                }
+                if settings.command == Command::Bench {
+                    settings.add_algorithms(args[i].as_str())?
+                } else {
+                    settings.set_algorithm(args[i].as_str())?
+                }
+            }
+            "-h" | "--heuristic" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!(
+                        "Need a heuristic: Use manhattan, hamming, linear_conflict, inversion_distance or exact"
+                    ));
+                }
+                if matches!(settings.command, Command::Bench | Command::Check) {
+                    settings.add_heuristics(args[i].as_str())?
+                } else {
+                    settings.set_heuristic(args[i].as_str())?
+                }
+            }
+            "-t" | "--timeout" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a time: Use numerical numbers"));
+                }
+                settings.set_timeout(args[i].as_str())?
+            }
+            "--svg" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a .svg file"));
+                }
+                settings.set_svg_path(args[i].as_str())?
+            }
+            "--html" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a .html file"));
+                }
+                settings.set_html_path(args[i].as_str())?
+            }
+            "-j" | "--jobs" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a number of jobs: Use numerical numbers"));
+                }
+                settings.set_jobs(args[i].as_str())?
+            }
+            "-n" | "--count" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a count: Use numerical numbers"));
+                }
+                settings.set_count(args[i].as_str())?
+            }
+            "--seed" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a seed: Use numerical numbers"));
+                }
+                settings.set_seed(args[i].as_str())?
+            }
+            "--csv" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a .csv file"));
+                }
+                settings.set_csv_path(args[i].as_str())?
+            }
+            "--goal" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a goal: Use snail or row_major"));
+                }
+                settings.set_goal(args[i].as_str())?
+            }
+            "--target" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a .txt file"));
+                }
+                settings.set_target(args[i].as_str())?
+            }
+            "--instances" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!(
+                        "Need instances: Use numbers or ranges, e.g. 1,3,5-7"
+                    ));
+                }
+                settings.add_instances(args[i].as_str())?
+            }
+            "--cache" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a path to the oracle file"));
+                }
+                settings.set_cache_path(args[i].as_str())?
+            }
+            "--depth" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a depth: Use numerical numbers"));
+                }
+                settings.set_depth(args[i].as_str())?
+            }
+            "--solutions" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a number of solutions: Use numerical numbers"));
+                }
+                settings.set_solutions(args[i].as_str())?
+            }
+            "--count-solutions" => settings.count_solutions = true,
+            "--optimize" => settings.optimize = true,
+            "--window" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a window: Use numerical numbers"));
+                }
+                settings.set_window(args[i].as_str())?
+            }
+            "--core" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a core size: Use numerical numbers"));
+                }
+                settings.set_core_size(args[i].as_str())?
+            }
+            "--threads" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a number of threads: Use numerical numbers"));
+                }
+                settings.set_threads(args[i].as_str())?
+            }
+            "--tt-size" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a table size: Use numerical numbers"));
+                }
+                settings.set_table_size(args[i].as_str())?
+            }
+            "--tt-policy" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a table policy: Use always or depth"));
+                }
+                settings.set_table_policy(args[i].as_str())?
+            }
+            "--node-budget" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a node budget: Use numerical numbers"));
+                }
+                settings.set_node_budget(args[i].as_str())?
+            }
+            "--tie-break" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!(
+                        "Need a tie-break: Use higher_g, lower_h, lifo, fifo or random"
+                    ));
+                }
+                settings.set_tie_break(args[i].as_str())?
+            }
+            "--open-list" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need an open list: Use heap or buckets"));
+                }
+                settings.set_open_list(args[i].as_str())?
+            }
+            "--beam-width" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a beam width: Use numerical numbers"));
+                }
+                settings.set_beam_width(args[i].as_str())?
+            }
+            "--dir" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!(
+                        "Need a directory: Use a path to the working directory"
+                    ));
+                }
+                settings.set_work_dir(args[i].as_str())?
+            }
+            "--chunk" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a chunk: Use numerical numbers"));
+                }
+                settings.set_chunk(args[i].as_str())?
+            }
+            "--checkpoint" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a path to the checkpoint file"));
+                }
+                settings.set_checkpoint_path(args[i].as_str())?
+            }
+            "--checkpoint-interval" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a checkpoint interval: Use numerical numbers"));
+                }
+                settings.set_checkpoint_interval(args[i].as_str())?
+            }
+            "--resume" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a file: Use a path to the checkpoint file"));
+                }
+                settings.set_resume_path(args[i].as_str())?
+            }
+            "--topology" => {
+                i += 1;
+                if i == len_args {
+                    return Err(anyhow!("Need a topology: Use plane or torus"));
+                }
+                settings.set_topology(args[i].as_str())?
+            }
+            "--verbose" => settings.verbose = true,
+            "--interactive" => settings.interactive = true,
+            _ if settings.command == Command::Batch => settings.add_input(arg),
+            _ if settings.command == Command::Bench && arg.trim().parse::<usize>().is_err() => {
+                settings.add_input(arg)
+            }
+            _ if settings.command == Command::Explore && arg.contains('x') => {
+                settings.set_board(arg)?
+            }
+            _ => match arg.trim().parse::<usize>() {
+                Ok(_) => settings.set_size(arg)?,
+                Err(_) => settings.set_text_path(arg)?,
+            },
+        }
+        i += 1;
+    }
+
+    settings.apply_default_setting()?;
+    Ok(Some(settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_normal() -> Result<()> {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "2".into()];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(2),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_one() {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "1".into()];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_size_zero() {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "0".into()];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_size_max() -> Result<()> {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "100".into()];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(
+            settings.puzzle_settings,
+            PuzzleSettings::Size(MAX_PUZZLE_SIZE)
+        );
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "101".into()];
+        assert!(parse_args(args).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_path_valid() -> Result<()> {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "test.txt".into()];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::TextPath("test.txt".into()),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_path_invalid() {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "test.py".into()];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_algorithm_astar() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "astar".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_algorithm_uniform_cost() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "uniform_cost".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::UniformCost),
+            Heuristic::None,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_algorithm_greedy() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "greedy".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::Greedy),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_algorithm_invalid() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "invalid".into(),
+        ];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_heuristic_manhattan() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-h".into(),
+            "manhattan".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_heuristic_invalid() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-h".into(),
+            "invalid".into(),
+        ];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_astar_with_heuristic() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "astar".into(),
+            "-h".into(),
+            "manhattan".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_greedy_with_heuristic() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "greedy".into(),
+            "-h".into(),
+            "manhattan".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::Size(3),
+            Some(Algorithm::Greedy),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_uniform_cost_with_heuristic() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "uniform_cost".into(),
+            "-h".into(),
+            "manhattan".into(),
+        ];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_complex_valid_middle() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "-a".into(),
+            "astar".into(),
+            "test.txt".into(),
+            "-h".into(),
+            "manhattan".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::TextPath("test.txt".into()),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_complex_valid_end() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "-a".into(),
+            "astar".into(),
+            "-h".into(),
+            "manhattan".into(),
+            "test.txt".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::TextPath("test.txt".into()),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            false,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_complex_invalid() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "-a".into(),
+            "-h".into(),
+            "manhattan".into(),
+            "test.txt".into(),
+        ];
+        let settings = parse_args(args);
+        assert!(settings.is_err());
+    }
+
+    #[test]
+    fn test_verbose() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "-a".into(),
+            "astar".into(),
+            "-h".into(),
+            "manhattan".into(),
+            "test.txt".into(),
+            "--verbose".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        let answer_settings = Settings::new(
+            PuzzleSettings::TextPath("test.txt".into()),
+            Some(Algorithm::AStar),
+            Heuristic::Manhattan,
+            None,
+            true,
+        );
+        assert_eq!(settings, answer_settings);
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_paths() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--svg".into(),
+            "start.svg".into(),
+            "--html".into(),
+            "solution.html".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.svg_path, Some("start.svg".into()));
+        assert_eq!(settings.html_path, Some("solution.html".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_paths_invalid() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--html".into(),
+            "solution.txt".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--svg".into(),
+        ];
+        assert!(parse_args(args).is_err());
+    }
+
+    #[test]
+    fn test_batch() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "batch".into(),
+            "puzzles".into(),
+            "-j".into(),
+            "4".into(),
+            "extra/*.txt".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Batch);
+        assert_eq!(
+            settings.inputs,
+            vec![String::from("puzzles"), String::from("extra/*.txt")]
+        );
+        assert_eq!(settings.jobs, Some(4));
+        assert_eq!(settings.algorithm, Some(Algorithm::AStar));
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_invalid() {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "batch".into()];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "batch".into(),
+            "puzzles".into(),
+            "-j".into(),
+            "0".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-j".into(),
+            "2".into(),
+        ];
+        assert!(parse_args(args).is_err());
+    }
+
+    #[test]
+    fn test_bench() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "bench".into(),
+            "3".into(),
+            "-n".into(),
+            "5".into(),
+            "--seed".into(),
+            "42".into(),
+            "-a".into(),
+            "astar,greedy".into(),
+            "-h".into(),
+            "manhattan".into(),
+            "-h".into(),
+            "linear_conflict".into(),
+            "--csv".into(),
+            "bench.csv".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Bench);
+        assert_eq!(settings.puzzle_settings, PuzzleSettings::Size(3));
+        assert_eq!(settings.count, Some(5));
+        assert_eq!(settings.seed, Some(42));
+        assert_eq!(
+            settings.algorithms,
+            vec![Algorithm::AStar, Algorithm::Greedy]
+        );
+        assert_eq!(
+            settings.heuristics,
+            vec![Heuristic::Manhattan, Heuristic::LinearConflict]
+        );
+        assert_eq!(settings.csv_path, Some("bench.csv".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bench_default() -> Result<()> {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "bench".into(), "4".into()];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.count, Some(10));
+        assert_eq!(settings.algorithms, vec![Algorithm::AStar]);
+        assert_eq!(settings.heuristics.len(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bench_invalid() {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "bench".into(),
+            "puzzles".into(),
+            "--seed".into(),
+            "1".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "bench".into(),
+            "3".into(),
+            "-a".into(),
+            "astar,astar".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-n".into(),
+            "3".into(),
+        ];
+        assert!(parse_args(args).is_err());
+    }
+
+    #[test]
+    fn test_goal() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--goal".into(),
+            "row_major".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.goal, Some(GoalSettings::RowMajor));
+        assert_eq!(settings.get_goal(3)?, Goal::row_major(3));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--goal".into(),
+            "spiral".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_korf() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "korf".into(),
+            "--instances".into(),
+            "1,3,5-7".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Korf);
+        assert_eq!(settings.instances, vec![1, 3, 5, 6, 7]);
+        assert_eq!(settings.goal, Some(GoalSettings::RowMajor));
+        assert_eq!(settings.algorithm, Some(Algorithm::IdaStar));
+        assert_eq!(settings.heuristic, Heuristic::InversionDistance);
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "korf".into()];
+        assert_eq!(parse_args(args)?.unwrap().instances.len(), 100);
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "korf".into(),
+            "--tie-break".into(),
+            "higher_g".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.algorithm, Some(Algorithm::AStar));
+        assert_eq!(settings.heuristic, Heuristic::InversionDistance);
+        Ok(())
+    }
+
+    #[test]
+    fn test_korf_invalid() {
+        for instances in ["0", "101", "7-5", "1,1", "a"] {
+            let args: Vec<String> = vec![
+                "target/debug/n-puzzle".into(),
+                "korf".into(),
+                "--instances".into(),
+                instances.into(),
+            ];
+            assert!(parse_args(args).is_err());
+        }
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "korf".into(),
+            "--goal".into(),
+            "snail".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--instances".into(),
+            "1".into(),
+        ];
+        assert!(parse_args(args).is_err());
+    }
+
     #[test]
-    fn test_algorithm_invalid() {
+    fn test_check() -> Result<()> {
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "check".into()];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Check);
+        assert_eq!(settings.puzzle_settings, PuzzleSettings::Size(3));
+        assert_eq!(settings.heuristics.len(), 4);
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "check".into(),
+            "4".into(),
+            "--depth".into(),
+            "10".into(),
+            "-n".into(),
+            "100".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.depth, Some(10));
+        assert_eq!(settings.count, Some(100));
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
             "3".into(),
+            "--depth".into(),
+            "10".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "check".into(),
             "-a".into(),
-            "invalid".into(),
+            "astar".into(),
         ];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+        assert!(parse_args(args).is_err());
+        Ok(())
     }
 
     #[test]
-    fn test_heuristic_manhattan() -> Result<()> {
+    fn test_oracle() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "oracle".into(),
+            "test.txt".into(),
+            "--cache".into(),
+            "oracle.bin".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Oracle);
+        assert_eq!(settings.cache_path, Some("oracle.bin".into()));
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "oracle".into(), "3".into()];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--cache".into(),
+            "oracle.bin".into(),
+        ];
+        assert!(parse_args(args).is_err());
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
             "3".into(),
             "-h".into(),
-            "manhattan".into(),
+            "exact".into(),
+            "--cache".into(),
+            "oracle.bin".into(),
+        ];
+        assert_eq!(parse_args(args)?.unwrap().heuristic, Heuristic::Exact);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "hint".into(),
+            "test.txt".into(),
+            "--interactive".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+        assert_eq!(settings.command, Command::Hint);
+        assert!(settings.interactive);
+        assert_eq!(settings.algorithm, Some(Algorithm::IdaStar));
+        assert_eq!(settings.heuristic, Heuristic::InversionDistance);
+        let args: Vec<String> = vec!["target/debug/n-puzzle".into(), "hint".into(), "3".into()];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--interactive".into(),
+        ];
+        assert!(parse_args(args).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_heuristic_invalid() {
+    fn test_solutions() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
             "3".into(),
-            "-h".into(),
-            "invalid".into(),
+            "--solutions".into(),
+            "10".into(),
+            "--count-solutions".into(),
         ];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.solutions, Some(10));
+        assert!(settings.count_solutions);
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--solutions".into(),
+            "0".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "batch".into(),
+            "puzzles".into(),
+            "--count-solutions".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        Ok(())
     }
 
     #[test]
-    fn test_astar_with_heuristic() -> Result<()> {
+    fn test_target() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
-            "3".into(),
-            "-a".into(),
-            "astar".into(),
-            "-h".into(),
-            "manhattan".into(),
+            "test.txt".into(),
+            "--target".into(),
+            "../puzzles/first_row_target.txt".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+        let target = settings.target.as_ref().unwrap();
+        assert!(target.is_partial());
+        assert_eq!(&settings.get_goal(3)?, target);
+        assert!(settings.get_goal(4).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--target".into(),
+            "../puzzles/first_row_target.txt".into(),
+            "--goal".into(),
+            "snail".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "test.txt".into(),
+            "--target".into(),
+            "target.svg".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "korf".into(),
+            "--target".into(),
+            "../puzzles/first_row_target.txt".into(),
+        ];
+        let error = parse_args(args).unwrap_err().to_string();
+        assert!(error.starts_with("Target is not available with the korf command"));
         Ok(())
     }
 
     #[test]
-    fn test_greedy_with_heuristic() -> Result<()> {
+    fn test_optimize() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
             "3".into(),
             "-a".into(),
             "greedy".into(),
-            "-h".into(),
-            "manhattan".into(),
+            "--optimize".into(),
+            "--window".into(),
+            "8".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::Size(3),
-            Some(Algorithm::Greedy),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+        assert!(settings.optimize);
+        assert_eq!(settings.window, Some(8));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "greedy".into(),
+            "--optimize".into(),
+            "--window".into(),
+            "33".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--window".into(),
+            "8".into(),
+        ];
+        assert!(parse_args(args).is_err());
         Ok(())
     }
 
     #[test]
-    fn test_uniform_cost_with_heuristic() {
+    fn test_core() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "8".into(),
+            "-a".into(),
+            "hierarchical".into(),
+            "--core".into(),
+            "4".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.algorithm, Some(Algorithm::Hierarchical));
+        assert_eq!(settings.heuristic, Heuristic::Manhattan);
+        assert_eq!(settings.core_size, Some(4));
+        for (algorithm, core) in [("hierarchical", "5"), ("astar", "3")] {
+            let args: Vec<String> = vec![
+                "target/debug/n-puzzle".into(),
+                "8".into(),
+                "-a".into(),
+                algorithm.into(),
+                "--core".into(),
+                core.into(),
+            ];
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_threads() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "4".into(),
+            "-a".into(),
+            "ida_star".into(),
+            "--threads".into(),
+            "8".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.threads, Some(8));
+        for (algorithm, threads) in [("ida_star", "0"), ("ida_star", "65"), ("astar", "8")] {
+            let args: Vec<String> = vec![
+                "target/debug/n-puzzle".into(),
+                "4".into(),
+                "-a".into(),
+                algorithm.into(),
+                "--threads".into(),
+                threads.into(),
+            ];
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_transposition_table() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "4".into(),
+            "-a".into(),
+            "ida_star".into(),
+            "--tt-size".into(),
+            "1000".into(),
+            "--tt-policy".into(),
+            "always".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.table_size, Some(1000));
+        assert_eq!(settings.table_policy, Some(Replacement::Always));
+        let invalid: [&[&str]; 4] = [
+            &["--tt-size", "1000", "--tt-policy", "never"],
+            &["--tt-policy", "depth"],
+            &["--tt-size", "1000", "--threads", "2"],
+            &["--tt-size", "1000", "-a", "astar"],
+        ];
+        for options in invalid {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into(), "4".into()];
+            if !options.contains(&"-a") {
+                args.extend(["-a".into(), "ida_star".into()]);
+            }
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_budget() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
             "3".into(),
             "-a".into(),
-            "uniform_cost".into(),
-            "-h".into(),
-            "manhattan".into(),
+            "sma_star".into(),
+            "--node-budget".into(),
+            "5000".into(),
         ];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.algorithm, Some(Algorithm::SmaStar));
+        assert_eq!(settings.node_budget, Some(5000));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "-a".into(),
+            "rbfs".into(),
+            "--node-budget".into(),
+            "5000".into(),
+        ];
+        assert!(parse_args(args).is_err());
+        Ok(())
     }
 
     #[test]
-    fn test_complex_valid_middle() -> Result<()> {
+    fn test_beam_width() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
+            "10".into(),
             "-a".into(),
-            "astar".into(),
-            "test.txt".into(),
-            "-h".into(),
-            "manhattan".into(),
+            "beam".into(),
+            "--beam-width".into(),
+            "50".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::TextPath("test.txt".into()),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+        assert_eq!(settings.algorithm, Some(Algorithm::Beam));
+        assert_eq!(settings.beam_width, Some(50));
+        for options in [
+            ["-a", "greedy", "--beam-width", "50"],
+            ["-a", "beam", "--beam-width", "0"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into(), "10".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
         Ok(())
     }
 
     #[test]
-    fn test_complex_valid_end() -> Result<()> {
+    fn test_tie_break() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "3".into(),
+            "--tie-break".into(),
+            "random".into(),
+            "--seed".into(),
+            "42".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.tie_break, Some(TieBreak::Random(42)));
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
+            "3".into(),
             "-a".into(),
-            "astar".into(),
-            "-h".into(),
-            "manhattan".into(),
-            "test.txt".into(),
+            "greedy".into(),
+            "--tie-break".into(),
+            "higher_g".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::TextPath("test.txt".into()),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            false,
-        );
-        assert_eq!(settings, answer_settings);
+        assert_eq!(settings.tie_break, Some(TieBreak::HigherG));
+        for options in [
+            vec!["--tie-break", "fifo", "--seed", "42"],
+            vec!["--tie-break", "newest"],
+            vec!["-a", "ida_star", "--tie-break", "lifo"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into(), "3".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
         Ok(())
     }
 
     #[test]
-    fn test_complex_invalid() {
+    fn test_open_list() -> Result<()> {
+        for command in [vec!["4"], vec!["bench", "4", "-n", "2"], vec!["korf"]] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into()];
+            args.extend(command.iter().map(|s| s.to_string()));
+            args.extend(["--open-list".into(), "buckets".into()]);
+            let settings = parse_args(args)?.unwrap();
+            assert_eq!(settings.open_list, Some(OpenList::Buckets));
+        }
+        for options in [
+            vec!["4", "-a", "ida_star", "--open-list", "buckets"],
+            vec!["4", "--open-list", "buckets", "--tie-break", "fifo"],
+            vec!["4", "--open-list", "array"],
+            vec!["check", "3", "--open-list", "heap"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_explore() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "explore".into(),
+            "3x5".into(),
+            "--dir".into(),
+            "work".into(),
+            "--chunk".into(),
+            "1000".into(),
+            "--depth".into(),
+            "20".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.command, Command::Explore);
+        assert_eq!(settings.board, Some((3, 5)));
+        assert_eq!(settings.work_dir, Some("work".into()));
+        assert_eq!(settings.chunk, Some(1000));
+        assert_eq!(settings.depth, Some(20));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "explore".into(),
+            "4".into(),
+            "--dir".into(),
+            "work".into(),
+        ];
+        assert_eq!(parse_args(args)?.unwrap().board, Some((4, 4)));
+        for options in [
+            vec!["explore", "3x5"],
+            vec!["explore", "--dir", "work"],
+            vec!["explore", "5", "--dir", "work"],
+            vec!["explore", "3x6", "--dir", "work"],
+            vec!["explore", "3x", "--dir", "work"],
+            vec!["explore", "3", "2x3", "--dir", "work"],
+            vec!["explore", "2x3", "--dir", "work", "--goal", "snail"],
+            vec!["explore", "3", "--dir", "work", "-a", "astar"],
+            vec!["3", "--dir", "work"],
+            vec!["check", "3", "--chunk", "10"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_checkpoint() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
+            "4".into(),
             "-a".into(),
-            "-h".into(),
-            "manhattan".into(),
-            "test.txt".into(),
+            "ida_star".into(),
+            "--checkpoint".into(),
+            "search.bin".into(),
+            "--checkpoint-interval".into(),
+            "60".into(),
         ];
-        let settings = parse_args(args);
-        assert!(settings.is_err());
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.checkpoint_path, Some("search.bin".into()));
+        assert_eq!(settings.checkpoint_interval, Some(60));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "--resume".into(),
+            "search.bin".into(),
+            "-t".into(),
+            "100".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(settings.resume_path, Some("search.bin".into()));
+        assert_eq!(settings.timeout, Some(100));
+        for options in [
+            vec!["4", "-a", "rbfs", "--checkpoint", "search.bin"],
+            vec![
+                "4",
+                "-a",
+                "ida_star",
+                "--threads",
+                "2",
+                "--checkpoint",
+                "search.bin",
+            ],
+            vec!["4", "--checkpoint-interval", "60"],
+            vec![
+                "4",
+                "--checkpoint",
+                "search.bin",
+                "--checkpoint-interval",
+                "0",
+            ],
+            vec!["4", "--resume", "search.bin"],
+            vec!["--resume", "search.bin", "-h", "hamming"],
+            vec!["--resume", "search.bin", "--goal", "row_major"],
+            vec!["batch", "puzzles", "--checkpoint", "search.bin"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
     }
 
     #[test]
-    fn test_verbose() -> Result<()> {
+    fn test_topology() -> Result<()> {
         let args: Vec<String> = vec![
             "target/debug/n-puzzle".into(),
+            "3".into(),
             "-a".into(),
-            "astar".into(),
-            "-h".into(),
-            "manhattan".into(),
-            "test.txt".into(),
-            "--verbose".into(),
+            "ida_star".into(),
+            "--topology".into(),
+            "torus".into(),
         ];
         let settings = parse_args(args)?.unwrap();
-        let answer_settings = Settings::new(
-            PuzzleSettings::TextPath("test.txt".into()),
-            Some(Algorithm::AStar),
-            Heuristic::Manhattan,
-            None,
-            true,
+        assert_eq!(settings.topology, Some(Topology::Torus));
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "check".into(),
+            "--topology".into(),
+            "torus".into(),
+        ];
+        let settings = parse_args(args)?.unwrap();
+        assert_eq!(
+            settings.heuristics,
+            vec![Heuristic::Manhattan, Heuristic::Hamming]
         );
-        assert_eq!(settings, answer_settings);
+        for options in [
+            vec!["3", "--topology", "klein"],
+            vec!["3", "--topology", "torus", "--topology", "plane"],
+            vec!["3", "-a", "constructive", "--topology", "torus"],
+            vec!["batch", "puzzles", "--topology", "torus"],
+            vec!["--resume", "search.bin", "--topology", "torus"],
+        ] {
+            let mut args: Vec<String> = vec!["target/debug/n-puzzle".into()];
+            args.extend(options.iter().map(|s| s.to_string()));
+            assert!(parse_args(args).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_exact() -> Result<()> {
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "check".into(),
+            "-h".into(),
+            "exact".into(),
+        ];
+        assert_eq!(
+            parse_args(args)?.unwrap().heuristics,
+            vec![Heuristic::Exact]
+        );
+        let args: Vec<String> = vec![
+            "target/debug/n-puzzle".into(),
+            "check".into(),
+            "4".into(),
+            "-h".into(),
+            "exact".into(),
+            "--depth".into(),
+            "2".into(),
+        ];
+        assert!(parse_args(args).is_err());
         Ok(())
     }
 }
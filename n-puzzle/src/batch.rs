@@ -0,0 +1,377 @@
+use crate::args::Settings;
+use crate::{Algorithm, Goal, Heuristic, OpenOptions, Output, Puzzle, Solver, Timeout};
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(Debug)]
+pub enum BatchStatus {
+    Solved(Box<Output>),
+    Unsolvable,
+    TimedOut,
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct BatchResult {
+    pub name: String,
+    pub status: BatchStatus,
+}
+
+impl std::fmt::Display for BatchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        match &self.status {
+            BatchStatus::Solved(output) => write!(
+                f,
+                "solved moves={} expanded={} time={:.6}s",
+                output.path.len(),
+                output.stats.nodes_expanded,
+                output.elapsed_time
+            ),
+            BatchStatus::Unsolvable => write!(f, "unsolvable"),
+            BatchStatus::TimedOut => write!(f, "timed out"),
+            BatchStatus::Failed(message) => write!(f, "failed ({})", message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BatchSummary {
+    pub results: Vec<BatchResult>,
+}
+
+impl BatchSummary {
+    fn count(&self, predicate: fn(&BatchStatus) -> bool) -> usize {
+        self.results.iter().filter(|r| predicate(&r.status)).count()
+    }
+
+    pub fn solved(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Solved(_)))
+    }
+
+    pub fn unsolvable(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Unsolvable))
+    }
+
+    pub fn timed_out(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::TimedOut))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Failed(_)))
+    }
+
+    fn solved_values(&self, value: fn(&Output) -> f64) -> Vec<f64> {
+        self.results
+            .iter()
+            .filter_map(|r| match &r.status {
+                BatchStatus::Solved(output) => Some(value(output)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn mean_moves(&self) -> Option<f64> {
+        mean(&self.solved_values(|output| output.path.len() as f64))
+    }
+
+    pub fn median_moves(&self) -> Option<f64> {
+        median(self.solved_values(|output| output.path.len() as f64))
+    }
+
+    pub fn mean_time(&self) -> Option<f64> {
+        mean(&self.solved_values(|output| output.elapsed_time))
+    }
+
+    pub fn median_time(&self) -> Option<f64> {
+        median(self.solved_values(|output| output.elapsed_time))
+    }
+}
+
+impl std::fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Puzzles: {}", self.results.len())?;
+        writeln!(f, "Solved: {}", self.solved())?;
+        writeln!(f, "Unsolvable: {}", self.unsolvable())?;
+        writeln!(f, "Timed out: {}", self.timed_out())?;
+        writeln!(f, "Failed: {}", self.failed())?;
+        match (self.mean_moves(), self.median_moves()) {
+            (Some(mean), Some(median)) => {
+                writeln!(f, "Moves: mean {:.2}, median {:.2}", mean, median)?
+            }
+            _ => writeln!(f, "Moves: -")?,
+        }
+        match (self.mean_time(), self.median_time()) {
+            (Some(mean), Some(median)) => writeln!(
+                f,
+                "Time: mean {:.6} seconds, median {:.6} seconds",
+                mean, median
+            ),
+            _ => writeln!(f, "Time: -"),
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[mid])
+    } else {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    }
+}
+
+/// Match a file name against a pattern with `*` and `?` wildcards
+fn matches_pattern(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|i| matches_pattern(&pattern[1..], &name[i..])),
+        Some('?') => !name.is_empty() && matches_pattern(&pattern[1..], &name[1..]),
+        Some(c) => name.first() == Some(c) && matches_pattern(&pattern[1..], &name[1..]),
+    }
+}
+
+fn list_directory(dir: &Path, pattern: &str) -> Result<Vec<String>> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name: Vec<char> = match path.file_name() {
+            Some(name) => name.to_string_lossy().chars().collect(),
+            None => continue,
+        };
+        if matches_pattern(&pattern, &name) {
+            files.push(path.to_string_lossy().to_string());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Expand a file, directory or glob pattern into a list of files
+fn expand_input(input: &str) -> Result<Vec<String>> {
+    let path = Path::new(input);
+    if path.is_dir() {
+        return list_directory(path, "*.txt");
+    }
+    if !input.contains(['*', '?']) {
+        return Ok(vec![input.to_string()]);
+    }
+    let pattern = match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None => return Err(anyhow!("Not a valid glob pattern: {}", input)),
+    };
+    let dir = match path.parent() {
+        Some(dir) if dir.as_os_str().is_empty() => Path::new("."),
+        Some(dir) => dir,
+        None => Path::new("."),
+    };
+    if dir.to_string_lossy().contains(['*', '?']) {
+        return Err(anyhow!(
+            "Not a valid glob pattern: {}. Wildcards are only allowed in the file name",
+            input
+        ));
+    }
+    let files = list_directory(dir, &pattern)?;
+    if files.is_empty() {
+        return Err(anyhow!("No file matches the pattern: {}", input));
+    }
+    Ok(files)
+}
+
+/// Read every puzzle of the given files, directories and glob patterns.
+///
+/// A file that cannot be parsed is kept as a single failed entry.
+pub fn collect_puzzles(inputs: &[String]) -> Result<Vec<(String, Result<Puzzle>)>> {
+    let mut puzzles = Vec::new();
+    for input in inputs {
+        for file in expand_input(input)? {
+            let file_puzzles = match Puzzle::parse_texts(&file) {
+                Ok(file_puzzles) => file_puzzles,
+                Err(e) => {
+                    puzzles.push((file, Err(e)));
+                    continue;
+                }
+            };
+            if file_puzzles.len() == 1 {
+                puzzles.push((file, Ok(file_puzzles.into_iter().next().unwrap())));
+                continue;
+            }
+            for (i, puzzle) in file_puzzles.into_iter().enumerate() {
+                puzzles.push((format!("{}#{}", file, i + 1), Ok(puzzle)));
+            }
+        }
+    }
+    Ok(puzzles)
+}
+
+fn solve_one(settings: &Settings, puzzle: &Result<Puzzle>) -> BatchStatus {
+    match puzzle {
+        Ok(puzzle) => match settings.get_goal(puzzle.get_size()) {
+            Ok(goal) => solve_puzzle(
+                puzzle,
+                &goal,
+                settings.algorithm.unwrap(),
+                settings.heuristic,
+                settings.timeout,
+                settings.open_options(),
+            ),
+            Err(e) => BatchStatus::Failed(e.to_string()),
+        },
+        Err(e) => BatchStatus::Failed(e.to_string()),
+    }
+}
+
+/// Solve a single puzzle, checking its solvability before searching
+pub(crate) fn solve_puzzle(
+    puzzle: &Puzzle,
+    goal: &Goal,
+    algorithm: Algorithm,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    options: OpenOptions,
+) -> BatchStatus {
+    match puzzle.is_solvable_to(goal) {
+        Ok(true) => {}
+        Ok(false) => return BatchStatus::Unsolvable,
+        Err(e) => return BatchStatus::Failed(e.to_string()),
+    }
+    let mut solver = Solver::new(algorithm, heuristic, puzzle.clone(), timeout, false);
+    solver.set_goal(goal.clone());
+    solver.set_open_options(options);
+    match solver.solve() {
+        Ok(output) => BatchStatus::Solved(Box::new(output)),
+        Err(e) if e.is::<Timeout>() => BatchStatus::TimedOut,
+        Err(e) => BatchStatus::Failed(e.to_string()),
+    }
+}
+
+/// Solve every puzzle of the batch with `settings.jobs` worker threads.
+///
+/// `on_result` is called as soon as each puzzle is finished, in completion order.
+pub fn solve_all<F: FnMut(&BatchResult)>(
+    settings: &Settings,
+    mut on_result: F,
+) -> Result<BatchSummary> {
+    let puzzles = collect_puzzles(&settings.inputs)?;
+    let jobs = settings.jobs.unwrap_or(1).clamp(1, puzzles.len().max(1));
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<BatchResult>> = (0..puzzles.len()).map(|_| None).collect();
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let next = &next;
+            let puzzles = &puzzles;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= puzzles.len() {
+                    break;
+                }
+                let (name, puzzle) = &puzzles[index];
+                let result = BatchResult {
+                    name: name.clone(),
+                    status: solve_one(settings, puzzle),
+                };
+                if sender.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+        for (index, result) in receiver {
+            on_result(&result);
+            results[index] = Some(result);
+        }
+    });
+
+    Ok(BatchSummary {
+        results: results.into_iter().flatten().collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<char> = pattern.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        matches_pattern(&pattern, &name)
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches("*.txt", "easy_4_puzzle.txt"));
+        assert!(matches("easy_?_puzzle.txt", "easy_4_puzzle.txt"));
+        assert!(matches("*", ""));
+        assert!(!matches("easy_?_puzzle.txt", "easy_10_puzzle.txt"));
+        assert!(!matches("*.txt", "puzzle.svg"));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![]), None);
+        assert_eq!(median(vec![3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(vec![4.0, 1.0, 2.0, 3.0]), Some(2.5));
+    }
+
+    #[test]
+    fn test_collect_puzzles() -> Result<()> {
+        let puzzles = collect_puzzles(&[
+            String::from("../puzzles/batch_puzzles.txt"),
+            String::from("../puzzles/one_move_*.txt"),
+        ])?;
+        let names: Vec<&str> = puzzles.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "../puzzles/batch_puzzles.txt#1",
+                "../puzzles/batch_puzzles.txt#2",
+                "../puzzles/batch_puzzles.txt#3",
+                "../puzzles/one_move_three.txt",
+            ]
+        );
+        assert!(puzzles.iter().all(|(_, puzzle)| puzzle.is_ok()));
+        assert!(collect_puzzles(&[String::from("../puzzles/nothing_*.txt")]).is_err());
+        let puzzles = collect_puzzles(&[String::from("../puzzles/error_one_puzzle.txt")])?;
+        assert!(puzzles[0].1.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_puzzle_timeout() -> Result<()> {
+        let puzzle = Puzzle::new(crate::PuzzleSettings::TextPath(
+            "../puzzles/difficult_3_puzzle.txt".into(),
+        ))?;
+        let status = solve_puzzle(
+            &puzzle,
+            &Goal::snail(3),
+            Algorithm::UniformCost,
+            Heuristic::None,
+            Some(0),
+            OpenOptions::default(),
+        );
+        assert!(matches!(status, BatchStatus::TimedOut));
+        Ok(())
+    }
+}
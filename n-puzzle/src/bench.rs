@@ -0,0 +1,265 @@
+use crate::args::Settings;
+use crate::batch::{collect_puzzles, solve_puzzle};
+use crate::{Algorithm, BatchStatus, Heuristic, Puzzle, PuzzleSettings};
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
+
+#[derive(Debug)]
+pub struct BenchRecord {
+    pub puzzle: String,
+    pub algorithm: Algorithm,
+    pub heuristic: Heuristic,
+    pub status: BatchStatus,
+    pub wall_time: f64,
+}
+
+impl BenchRecord {
+    fn status_name(&self) -> &str {
+        match self.status {
+            BatchStatus::Solved(_) => "solved",
+            BatchStatus::Unsolvable => "unsolvable",
+            BatchStatus::TimedOut => "timed_out",
+            BatchStatus::Failed(_) => "failed",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BenchReport {
+    pub records: Vec<BenchRecord>,
+}
+
+impl BenchReport {
+    /// Comparison table with one row per algorithm and heuristic,
+    /// averaged over the solved puzzles
+    pub fn to_table(&self) -> String {
+        let mut text = format!(
+            "{:<14} {:<20} {:>9} {:>9} {:>12} {:>12} {:>12} {:>7} {:>10}\n",
+            "algorithm",
+            "heuristic",
+            "solved",
+            "moves",
+            "expanded",
+            "memory",
+            "time(s)",
+            "ebf",
+            "penetrance"
+        );
+        let mut combinations: Vec<(Algorithm, Heuristic)> = Vec::new();
+        for record in &self.records {
+            if !combinations.contains(&(record.algorithm, record.heuristic)) {
+                combinations.push((record.algorithm, record.heuristic));
+            }
+        }
+        for (algorithm, heuristic) in combinations {
+            let records: Vec<&BenchRecord> = self
+                .records
+                .iter()
+                .filter(|r| r.algorithm == algorithm && r.heuristic == heuristic)
+                .collect();
+            let solved: Vec<(&BenchRecord, &crate::Output)> = records
+                .iter()
+                .filter_map(|r| match &r.status {
+                    BatchStatus::Solved(output) => Some((*r, output.as_ref())),
+                    _ => None,
+                })
+                .collect();
+            let average = |value: &dyn Fn(&BenchRecord, &crate::Output) -> f64, precision| {
+                if solved.is_empty() {
+                    return String::from("-");
+                }
+                let total: f64 = solved.iter().map(|(r, output)| value(r, output)).sum();
+                format!("{:.*}", precision, total / solved.len() as f64)
+            };
+            let average_ratio = |ratio: fn(&crate::Output) -> Option<f64>, precision| {
+                let ratios: Vec<f64> = solved.iter().filter_map(|(_, o)| ratio(o)).collect();
+                if ratios.is_empty() {
+                    return String::from("-");
+                }
+                let total: f64 = ratios.iter().sum();
+                format!("{:.*}", precision, total / ratios.len() as f64)
+            };
+            text += format!(
+                "{:<14} {:<20} {:>9} {:>9} {:>12} {:>12} {:>12} {:>7} {:>10}\n",
+                algorithm.to_string(),
+                heuristic.to_string(),
+                format!("{}/{}", solved.len(), records.len()),
+                average(&|_, output| output.path.len() as f64, 2),
+                average(&|_, output| output.stats.nodes_expanded as f64, 2),
+                average(&|_, output| output.complexity_in_size as f64, 2),
+                average(&|r, _| r.wall_time, 6),
+                average_ratio(crate::Output::effective_branching_factor, 3),
+                average_ratio(crate::Output::penetrance, 6)
+            )
+            .as_str();
+        }
+        text
+    }
+
+    /// One line per puzzle, algorithm and heuristic
+    pub fn to_csv(&self) -> String {
+        let mut text = String::from(
+            "puzzle,algorithm,heuristic,status,moves,nodes_generated,nodes_expanded,duplicates,reopened,max_open,max_closed,max_memory,heuristic_evaluations,wall_time,effective_branching_factor,penetrance\n",
+        );
+        for record in &self.records {
+            let metrics = match &record.status {
+                BatchStatus::Solved(output) => format!(
+                    "{},{},{},{},{},{},{},{},{}",
+                    output.path.len(),
+                    output.stats.nodes_generated,
+                    output.stats.nodes_expanded,
+                    output.stats.duplicates,
+                    output.stats.reopened,
+                    output.stats.max_open,
+                    output.stats.max_closed,
+                    output.stats.max_memory,
+                    output.stats.heuristic_evaluations
+                ),
+                _ => String::from(",,,,,,,,"),
+            };
+            let ratio = |value: Option<f64>| {
+                value
+                    .map(|value| format!("{:.6}", value))
+                    .unwrap_or_default()
+            };
+            let ratios = match &record.status {
+                BatchStatus::Solved(output) => format!(
+                    "{},{}",
+                    ratio(output.effective_branching_factor()),
+                    ratio(output.penetrance())
+                ),
+                _ => String::from(","),
+            };
+            text += format!(
+                "{},{},{},{},{},{:.6},{}\n",
+                csv_field(&record.puzzle),
+                record.algorithm,
+                record.heuristic,
+                record.status_name(),
+                metrics,
+                record.wall_time,
+                ratios
+            )
+            .as_str();
+        }
+        text
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Every requested algorithm and heuristic pair.
+/// Algorithms without heuristic are run only once.
+fn combinations(settings: &Settings) -> Vec<(Algorithm, Heuristic)> {
+    let mut combinations = Vec::new();
+    for algorithm in &settings.algorithms {
+        if !algorithm.is_heuristic() {
+            combinations.push((*algorithm, Heuristic::None));
+            continue;
+        }
+        for heuristic in &settings.heuristics {
+            combinations.push((*algorithm, *heuristic));
+        }
+    }
+    combinations
+}
+
+/// Puzzles given as inputs, followed by the seeded random puzzles
+fn bench_puzzles(settings: &Settings) -> Result<Vec<(String, Result<Puzzle>)>> {
+    let mut puzzles = collect_puzzles(&settings.inputs)?;
+    if let PuzzleSettings::Size(size) = settings.puzzle_settings {
+        if size != 0 {
+            let seed = settings.seed.unwrap_or_else(|| rand::thread_rng().gen());
+            let mut rng = StdRng::seed_from_u64(seed);
+            for i in 0..settings.count.unwrap_or(1) {
+                let goal = settings.get_goal(size)?;
+                let puzzle = Puzzle::generate_solvable_with_rng(size, &goal, &mut rng);
+                puzzles.push((format!("random-{}-seed{}#{}", size, seed, i + 1), puzzle));
+            }
+        }
+    }
+    Ok(puzzles)
+}
+
+pub fn run(settings: &Settings) -> Result<BenchReport> {
+    let puzzles = bench_puzzles(settings)?;
+    let mut records = Vec::new();
+    for (name, puzzle) in &puzzles {
+        for (algorithm, heuristic) in combinations(settings) {
+            let start = Instant::now();
+            let status = match puzzle {
+                Ok(puzzle) => match settings.get_goal(puzzle.get_size()) {
+                    Ok(goal) => solve_puzzle(
+                        puzzle,
+                        &goal,
+                        algorithm,
+                        heuristic,
+                        settings.timeout,
+                        settings.open_options(),
+                    ),
+                    Err(e) => BatchStatus::Failed(e.to_string()),
+                },
+                Err(e) => BatchStatus::Failed(e.to_string()),
+            };
+            records.push(BenchRecord {
+                puzzle: name.clone(),
+                algorithm,
+                heuristic,
+                status,
+                wall_time: start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+    Ok(BenchReport { records })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("puzzles/a.txt"), "puzzles/a.txt");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn test_combinations() {
+        let mut settings = Settings::new_default();
+        settings.algorithms = vec![Algorithm::AStar, Algorithm::UniformCost];
+        settings.heuristics = vec![Heuristic::Manhattan, Heuristic::Hamming];
+        assert_eq!(
+            combinations(&settings),
+            vec![
+                (Algorithm::AStar, Heuristic::Manhattan),
+                (Algorithm::AStar, Heuristic::Hamming),
+                (Algorithm::UniformCost, Heuristic::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bench_puzzles_seeded() -> Result<()> {
+        let mut settings = Settings::new_default();
+        settings.puzzle_settings = PuzzleSettings::Size(3);
+        settings.count = Some(3);
+        settings.seed = Some(7);
+        let puzzles1 = bench_puzzles(&settings)?;
+        let puzzles2 = bench_puzzles(&settings)?;
+        assert_eq!(puzzles1.len(), 3);
+        assert_eq!(puzzles1[2].0, "random-3-seed7#3");
+        for ((_, puzzle1), (_, puzzle2)) in puzzles1.iter().zip(puzzles2.iter()) {
+            assert_eq!(puzzle1.as_ref().unwrap(), puzzle2.as_ref().unwrap());
+        }
+        Ok(())
+    }
+}
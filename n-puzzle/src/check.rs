@@ -0,0 +1,311 @@
+use crate::args::Settings;
+use crate::{Goal, Heuristic, Move, Puzzle, PuzzleSettings};
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+
+/// Number of offending boards kept for each kind of violation
+const MAX_EXAMPLES: usize = 5;
+
+/// Default search depth of the distance table for boards larger than 3x3
+pub(crate) const DEFAULT_DEPTH: usize = 14;
+
+/// A state whose estimate is larger than its optimal distance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inadmissible {
+    pub puzzle: Puzzle,
+    pub estimate: usize,
+    pub distance: usize,
+}
+
+/// A state whose estimate drops by more than one move after a single move
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inconsistent {
+    pub puzzle: Puzzle,
+    pub estimate: usize,
+    pub neighbor: Puzzle,
+    pub neighbor_estimate: usize,
+}
+
+#[derive(Debug)]
+pub struct HeuristicCheck {
+    pub heuristic: Heuristic,
+    pub checked: usize,
+    pub inadmissible: usize,
+    pub inconsistent: usize,
+    pub max_overestimate: usize,
+    pub inadmissible_examples: Vec<Inadmissible>,
+    pub inconsistent_examples: Vec<Inconsistent>,
+}
+
+impl HeuristicCheck {
+    pub fn is_valid(&self) -> bool {
+        self.inadmissible == 0 && self.inconsistent == 0
+    }
+}
+
+impl std::fmt::Display for HeuristicCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}: checked {} states, inadmissible {} (max overestimate {}), inconsistent {}",
+            self.heuristic,
+            self.checked,
+            self.inadmissible,
+            self.max_overestimate,
+            self.inconsistent
+        )?;
+        for example in &self.inadmissible_examples {
+            writeln!(
+                f,
+                "  inadmissible: h={} distance={}",
+                example.estimate, example.distance
+            )?;
+            write!(f, "{}", example.puzzle)?;
+        }
+        for example in &self.inconsistent_examples {
+            writeln!(
+                f,
+                "  inconsistent: h={} -> h={} after one move",
+                example.estimate, example.neighbor_estimate
+            )?;
+            write!(f, "{}", example.puzzle)?;
+            writeln!(f, "  ->")?;
+            write!(f, "{}", example.neighbor)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CheckReport {
+    /// Whether every state of the board was checked
+    pub exhaustive: bool,
+    /// Number of moves from the goal the checked states are within, any number without one
+    pub depth: Option<usize>,
+    pub checks: Vec<HeuristicCheck>,
+}
+
+impl CheckReport {
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| !check.is_valid()).count()
+    }
+}
+
+impl std::fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.exhaustive {
+            writeln!(f, "Every solvable state was checked.")?;
+        }
+        if let Some(depth) = self.depth {
+            writeln!(
+                f,
+                "Only the states within {} moves of the goal were checked.",
+                depth
+            )?;
+        }
+        for check in &self.checks {
+            write!(f, "{}", check)?;
+        }
+        Ok(())
+    }
+}
+
+fn neighbors(puzzle: &Puzzle) -> Vec<Puzzle> {
+    Move::list()
+        .into_iter()
+        .filter_map(|m| {
+            let mut neighbor = puzzle.clone();
+            neighbor.move_blank(m).ok().map(|_| neighbor)
+        })
+        .collect()
+}
+
+/// Optimal distance of every state within `max_depth` moves of the goal,
+/// found by a breadth-first search from the goal, in order of distance.
+pub(crate) fn distances_from(goal: &Goal, max_depth: Option<usize>) -> Vec<(Puzzle, usize)> {
+    let mut visited = HashSet::new();
+    let mut states = Vec::new();
+    let mut queue = VecDeque::new();
+    visited.insert(goal.target().clone());
+    queue.push_back((goal.target().clone(), 0));
+    while let Some((puzzle, distance)) = queue.pop_front() {
+        let expand = match max_depth {
+            Some(depth) => distance < depth,
+            None => true,
+        };
+        if expand {
+            for neighbor in neighbors(&puzzle) {
+                if visited.insert(neighbor.clone()) {
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+        states.push((puzzle, distance));
+    }
+    states
+}
+
+pub(crate) fn check_heuristic(
+    heuristic: Heuristic,
+    goal: &Goal,
+    states: &[&(Puzzle, usize)],
+) -> Result<HeuristicCheck> {
+    let func = heuristic.get_heuristic()?;
+    let goal = &heuristic.prepare_goal(goal)?;
+    let mut check = HeuristicCheck {
+        heuristic,
+        checked: states.len(),
+        inadmissible: 0,
+        inconsistent: 0,
+        max_overestimate: 0,
+        inadmissible_examples: Vec::new(),
+        inconsistent_examples: Vec::new(),
+    };
+    for (puzzle, distance) in states {
+        let estimate = func(puzzle, goal);
+        if estimate > *distance {
+            check.inadmissible += 1;
+            check.max_overestimate = check.max_overestimate.max(estimate - distance);
+            if check.inadmissible_examples.len() < MAX_EXAMPLES {
+                check.inadmissible_examples.push(Inadmissible {
+                    puzzle: puzzle.clone(),
+                    estimate,
+                    distance: *distance,
+                });
+            }
+        }
+        for neighbor in neighbors(puzzle) {
+            let neighbor_estimate = func(&neighbor, goal);
+            if estimate > neighbor_estimate + 1 {
+                check.inconsistent += 1;
+                if check.inconsistent_examples.len() < MAX_EXAMPLES {
+                    check.inconsistent_examples.push(Inconsistent {
+                        puzzle: puzzle.clone(),
+                        estimate,
+                        neighbor,
+                        neighbor_estimate,
+                    });
+                }
+            }
+        }
+    }
+    Ok(check)
+}
+
+/// Compare every requested heuristic with the optimal distances.
+///
+/// Boards up to 3x3 are enumerated exhaustively unless a depth is given.
+/// Larger boards only know the distances of the states within `settings.depth` moves of the goal,
+/// so the states far from the goal are never checked.
+/// When a count is given, that many states are sampled at random from the known states.
+pub fn run(settings: &Settings) -> Result<CheckReport> {
+    let size = match settings.puzzle_settings {
+        PuzzleSettings::Size(size) => size,
+        _ => return Err(anyhow!("Need a size.")),
+    };
+    let goal = settings.get_goal(size)?;
+    goal.check_complete()?;
+    // The states are found from the target, so that they share its topology
+    let mut target = goal.target().clone();
+    target.set_topology(settings.topology.unwrap_or_default());
+    let goal = Goal::from_puzzle(target);
+    let depth = match settings.depth {
+        Some(depth) => Some(depth),
+        None if size <= 3 => None,
+        None => Some(DEFAULT_DEPTH),
+    };
+    let states = distances_from(&goal, depth);
+    let mut sample: Vec<&(Puzzle, usize)> = states.iter().collect();
+    let mut exhaustive = depth.is_none();
+    if let Some(count) = settings.count {
+        if count < sample.len() {
+            let seed = settings.seed.unwrap_or_else(|| rand::thread_rng().gen());
+            let mut rng = StdRng::seed_from_u64(seed);
+            sample = sample.choose_multiple(&mut rng, count).copied().collect();
+            exhaustive = false;
+        }
+    }
+    let mut checks = Vec::new();
+    for heuristic in &settings.heuristics {
+        checks.push(check_heuristic(*heuristic, &goal, &sample)?);
+    }
+    Ok(CheckReport {
+        exhaustive,
+        depth,
+        checks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distances_from() {
+        // 4! / 2 solvable states of the 2x2 puzzle
+        let states = distances_from(&Goal::snail(2), None);
+        assert_eq!(states.len(), 12);
+        assert_eq!(states[0], (Puzzle::new_answer(2), 0));
+        assert_eq!(states.iter().map(|(_, d)| *d).max(), Some(6));
+        let states = distances_from(&Goal::snail(3), Some(2));
+        assert_eq!(states.len(), 1 + 4 + 8);
+    }
+
+    #[test]
+    fn test_check_heuristic() -> Result<()> {
+        let goal = Goal::snail(3);
+        let states = distances_from(&goal, Some(8));
+        let states: Vec<&(Puzzle, usize)> = states.iter().collect();
+        let check = check_heuristic(Heuristic::InversionDistance, &goal, &states)?;
+        assert!(check.is_valid());
+        assert_eq!(check.checked, states.len());
+        // The blank counted by manhattan makes it overestimate
+        let check = check_heuristic(Heuristic::Manhattan, &goal, &states)?;
+        assert!(check.inadmissible > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_torus() -> Result<()> {
+        let mut target = Puzzle::new_answer(3);
+        target.set_topology(crate::Topology::Torus);
+        let goal = Goal::from_puzzle(target);
+        let states = distances_from(&goal, Some(10));
+        let states: Vec<&(Puzzle, usize)> = states.iter().collect();
+        for heuristic in [Heuristic::Manhattan, Heuristic::Hamming] {
+            assert!(check_heuristic(heuristic, &goal, &states)?.is_valid());
+        }
+        let check = check_heuristic(Heuristic::LinearConflict, &goal, &states)?;
+        assert!(check.inadmissible > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_inconsistent() -> Result<()> {
+        let goal = Goal::snail(3);
+        let puzzle = Puzzle::new_from_state(vec![vec![0, 2, 1], vec![4, 8, 3], vec![7, 6, 5]])?;
+        let distance = crate::Solver::new(
+            crate::Algorithm::AStar,
+            Heuristic::Exact,
+            puzzle.clone(),
+            None,
+            false,
+        )
+        .solve()?
+        .path
+        .len();
+        let state = (puzzle, distance);
+        let check = check_heuristic(Heuristic::LinearConflict, &goal, &[&state])?;
+        assert_eq!(check.inadmissible, 0);
+        assert_eq!(check.inconsistent, 1);
+        assert_eq!(check.inconsistent_examples[0].estimate, 12);
+        assert_eq!(check.inconsistent_examples[0].neighbor_estimate, 10);
+        assert!(!check.is_valid());
+        let check = check_heuristic(Heuristic::Manhattan, &goal, &[&state])?;
+        assert!(check.is_valid());
+        Ok(())
+    }
+}
@@ -0,0 +1,567 @@
+use crate::args::Settings;
+use crate::{GoalSettings, Pos, Topology};
+use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// A state is packed in a u64 with 4 bits per cell, which is enough for 4x4 and 3x5
+pub(crate) const MAX_CELLS: usize = 16;
+/// States generated in memory before they are sorted and written to a run file
+pub(crate) const DEFAULT_CHUNK: usize = 1 << 22;
+const MANIFEST: &str = "manifest";
+const MANIFEST_HEADER: &str = "n-puzzle explore 1";
+
+/// Number of states at each depth of a breadth-first search
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExploreReport {
+    pub width: usize,
+    pub height: usize,
+    pub topology: Topology,
+    /// States at each depth, from the start state at depth 0
+    pub counts: Vec<u64>,
+    /// Whether every state reachable from the start was found
+    pub complete: bool,
+    /// Last non-empty layer already in the working directory when the search was resumed
+    pub resumed_depth: Option<usize>,
+}
+
+impl ExploreReport {
+    pub fn states(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    fn board(&self) -> String {
+        match self.topology {
+            Topology::Plane => format!("{}x{} board", self.width, self.height),
+            Topology::Torus => format!("{}x{} torus board", self.width, self.height),
+        }
+    }
+}
+
+impl std::fmt::Display for ExploreReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(depth) = self.resumed_depth {
+            writeln!(f, "Resumed after depth {}.", depth)?;
+        }
+        let depth = self.counts.len() - 1;
+        if self.complete {
+            writeln!(
+                f,
+                "Every reachable state of the {} was found: {} states, at most {} moves from the start.",
+                self.board(),
+                self.states(),
+                depth
+            )
+        } else {
+            writeln!(
+                f,
+                "{} states of the {} within {} moves of the start.",
+                self.states(),
+                self.board(),
+                depth
+            )
+        }
+    }
+}
+
+/// Sorted states read from a layer or run file
+struct StateReader {
+    reader: BufReader<File>,
+}
+
+impl StateReader {
+    fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            reader: BufReader::new(File::open(path)?),
+        })
+    }
+
+    fn next(&mut self) -> Result<Option<u64>> {
+        let mut bytes = [0; 8];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => Ok(Some(u64::from_le_bytes(bytes))),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Membership test against a sorted file, for states queried in increasing order
+struct SortedFilter {
+    reader: StateReader,
+    head: Option<u64>,
+}
+
+impl SortedFilter {
+    fn open(path: &Path) -> Result<Self> {
+        let mut reader = StateReader::open(path)?;
+        let head = reader.next()?;
+        Ok(Self { reader, head })
+    }
+
+    fn contains(&mut self, state: u64) -> Result<bool> {
+        while self.head.is_some_and(|head| head < state) {
+            self.head = self.reader.next()?;
+        }
+        Ok(self.head == Some(state))
+    }
+}
+
+fn write_states(path: &Path, states: impl IntoIterator<Item = u64>) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for state in states {
+        writer.write_all(&state.to_le_bytes())?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Breadth-first search whose layers are kept in sorted files instead of memory.
+///
+/// The successors of a layer are sorted in chunks written to run files, then merged while removing
+/// the duplicates and the states of the two previous layers (delayed duplicate detection).
+/// Every layer is renamed into place once complete, so an interrupted search resumes from the
+/// last complete layer of its working directory.
+pub struct Explorer {
+    dir: PathBuf,
+    width: usize,
+    height: usize,
+    topology: Topology,
+    start: u64,
+    chunk: usize,
+    /// States in each layer already in the working directory
+    counts: Vec<u64>,
+    resumed_depth: Option<usize>,
+}
+
+impl Explorer {
+    /// Search of the `width` x `height` board from `start`, its cells in reading order with 0 for the blank.
+    /// A working directory holding the search of the same board and start state is resumed.
+    pub fn new(
+        dir: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        start: &[usize],
+    ) -> Result<Self> {
+        Self::new_on(dir, width, height, start, Topology::Plane)
+    }
+
+    /// Search of a board of the given topology, where the blank may wrap at the edges
+    pub fn new_on(
+        dir: impl AsRef<Path>,
+        width: usize,
+        height: usize,
+        start: &[usize],
+        topology: Topology,
+    ) -> Result<Self> {
+        let cells = width * height;
+        if width < 2 || height < 2 || cells > MAX_CELLS {
+            return Err(anyhow!(
+                "Not a valid board: {}x{}. Boards need at least 2 rows and columns and at most {} cells",
+                width,
+                height,
+                MAX_CELLS
+            ));
+        }
+        let mut sorted = start.to_vec();
+        sorted.sort_unstable();
+        if sorted != (0..cells).collect::<Vec<_>>() {
+            return Err(anyhow!(
+                "The start state must hold every value from 0 to {} once.",
+                cells - 1
+            ));
+        }
+        let start = start
+            .iter()
+            .enumerate()
+            .fold(0, |state, (i, &value)| state | (value as u64) << (4 * i));
+        let mut explorer = Self {
+            dir: dir.as_ref().to_path_buf(),
+            width,
+            height,
+            topology,
+            start,
+            chunk: DEFAULT_CHUNK,
+            counts: Vec::new(),
+            resumed_depth: None,
+        };
+        explorer.open()?;
+        Ok(explorer)
+    }
+
+    /// States sorted in memory at once, which bounds the memory used by the search
+    pub fn set_chunk(&mut self, chunk: usize) {
+        self.chunk = chunk.max(1);
+    }
+
+    fn manifest(&self) -> String {
+        let cells: Vec<String> = (0..self.width * self.height)
+            .map(|i| ((self.start >> (4 * i)) & 0xf).to_string())
+            .collect();
+        // The plane boards keep the manifest written before the torus boards
+        let topology = match self.topology {
+            Topology::Plane => "",
+            Topology::Torus => " torus",
+        };
+        format!(
+            "{}\n{}x{}{}\n{}\n",
+            MANIFEST_HEADER,
+            self.width,
+            self.height,
+            topology,
+            cells.join(" ")
+        )
+    }
+
+    fn layer_path(&self, depth: usize) -> PathBuf {
+        self.dir.join(format!("layer-{}.bin", depth))
+    }
+
+    /// Create the working directory, or check and clean the one of an interrupted search
+    fn open(&mut self) -> Result<()> {
+        let manifest_path = self.dir.join(MANIFEST);
+        match fs::read_to_string(&manifest_path) {
+            Ok(manifest) => {
+                if !manifest.starts_with(&format!("{}\n", MANIFEST_HEADER)) {
+                    return Err(anyhow!(
+                        "Not a valid explore directory: {}. It was written by another version",
+                        self.dir.display()
+                    ));
+                }
+                if manifest != self.manifest() {
+                    return Err(anyhow!(
+                        "The directory {} holds the search of another board or start state.",
+                        self.dir.display()
+                    ));
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                fs::create_dir_all(&self.dir)?;
+                fs::write(&manifest_path, self.manifest())?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        // Run files and unfinished layers of an interrupted search
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if name.starts_with("run-") || name.ends_with(".tmp") {
+                fs::remove_file(&path)?;
+            }
+        }
+        while let Ok(metadata) = fs::metadata(self.layer_path(self.counts.len())) {
+            self.counts.push(metadata.len() / 8);
+        }
+        if self.counts.is_empty() {
+            write_states(&self.layer_path(0), [self.start])?;
+            self.counts.push(1);
+        } else {
+            let empty = self.counts.iter().filter(|&&count| count == 0).count();
+            self.resumed_depth = Some(self.counts.len() - 1 - empty);
+        }
+        Ok(())
+    }
+
+    fn neighbors(&self, state: u64, mut f: impl FnMut(u64)) {
+        let width = self.width;
+        let cells = width * self.height;
+        let blank = match (0..cells).find(|i| (state >> (4 * i)) & 0xf == 0) {
+            Some(blank) => blank,
+            None => return,
+        };
+        let (x, y) = (blank % width, blank / width);
+        let torus = self.topology == Topology::Torus;
+        let targets = [
+            (y > 0).then(|| blank - width),
+            (y + 1 < self.height).then(|| blank + width),
+            (x > 0).then(|| blank - 1),
+            (x + 1 < width).then(|| blank + 1),
+            // Wrapping moves, which lead to the same states as the moves above on a side of 2
+            (torus && y == 0 && self.height > 2).then(|| blank + cells - width),
+            (torus && y + 1 == self.height && self.height > 2).then(|| blank + width - cells),
+            (torus && x == 0 && width > 2).then(|| blank + width - 1),
+            (torus && x + 1 == width && width > 2).then(|| blank + 1 - width),
+        ];
+        for target in targets.into_iter().flatten() {
+            let value = (state >> (4 * target)) & 0xf;
+            f(state & !(0xf << (4 * target)) | value << (4 * blank));
+        }
+    }
+
+    /// Write the layer after `depth` and return its number of states
+    fn expand(&self, depth: usize) -> Result<u64> {
+        let mut runs = Vec::new();
+        let mut buffer = Vec::with_capacity(self.chunk.min(DEFAULT_CHUNK));
+        let mut write_run = |buffer: &mut Vec<u64>| -> Result<()> {
+            buffer.sort_unstable();
+            buffer.dedup();
+            let path = self.dir.join(format!("run-{}.bin", runs.len()));
+            write_states(&path, buffer.drain(..))?;
+            runs.push(path);
+            Ok(())
+        };
+        let mut layer = StateReader::open(&self.layer_path(depth))?;
+        while let Some(state) = layer.next()? {
+            self.neighbors(state, |next| buffer.push(next));
+            if buffer.len() >= self.chunk {
+                write_run(&mut buffer)?;
+            }
+        }
+        if !buffer.is_empty() {
+            write_run(&mut buffer)?;
+        }
+        drop(buffer);
+
+        // The neighbors of a layer are in the previous, the same or the next layer
+        let mut previous = vec![SortedFilter::open(&self.layer_path(depth))?];
+        if depth > 0 {
+            previous.push(SortedFilter::open(&self.layer_path(depth - 1))?);
+        }
+        let mut readers = runs
+            .iter()
+            .map(|path| StateReader::open(path))
+            .collect::<Result<Vec<_>>>()?;
+        let mut heap = BinaryHeap::new();
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(state) = reader.next()? {
+                heap.push(Reverse((state, i)));
+            }
+        }
+        let tmp_path = self.dir.join(format!("layer-{}.tmp", depth + 1));
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        let mut last = None;
+        let mut count = 0;
+        while let Some(Reverse((state, i))) = heap.pop() {
+            if let Some(next) = readers[i].next()? {
+                heap.push(Reverse((next, i)));
+            }
+            if last == Some(state) {
+                continue;
+            }
+            last = Some(state);
+            let mut known = false;
+            for filter in previous.iter_mut() {
+                known |= filter.contains(state)?;
+            }
+            if !known {
+                writer.write_all(&state.to_le_bytes())?;
+                count += 1;
+            }
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp_path, self.layer_path(depth + 1))?;
+        for path in runs {
+            fs::remove_file(path)?;
+        }
+        Ok(count)
+    }
+
+    /// Search until every state is found or up to `max_depth`, calling `on_layer` with the depth and
+    /// number of states of every layer, including the ones already in the working directory
+    pub fn run(
+        &mut self,
+        max_depth: Option<usize>,
+        mut on_layer: impl FnMut(usize, u64),
+    ) -> Result<ExploreReport> {
+        let within = |depth: usize| match max_depth {
+            Some(max_depth) => depth <= max_depth,
+            None => true,
+        };
+        for (depth, &count) in self.counts.iter().enumerate() {
+            if count > 0 && within(depth) {
+                on_layer(depth, count);
+            }
+        }
+        loop {
+            let depth = self.counts.len() - 1;
+            if self.counts[depth] == 0 || !within(depth + 1) {
+                break;
+            }
+            let count = self.expand(depth)?;
+            self.counts.push(count);
+            if count > 0 {
+                on_layer(depth + 1, count);
+            }
+        }
+        // An empty layer marks the end of the search
+        let complete = self
+            .counts
+            .iter()
+            .position(|&count| count == 0)
+            .is_some_and(|depth| within(depth - 1));
+        let counts = self
+            .counts
+            .iter()
+            .enumerate()
+            .take_while(|&(depth, &count)| count > 0 && within(depth))
+            .map(|(_, &count)| count)
+            .collect();
+        Ok(ExploreReport {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+            counts,
+            complete,
+            resumed_depth: self.resumed_depth,
+        })
+    }
+}
+
+/// Start state of the explore command: the goal of square boards, or the row-major goal
+/// with the blank first for the other boards
+fn start_cells(settings: &Settings, width: usize, height: usize) -> Result<Vec<usize>> {
+    if width != height {
+        if settings.target.is_some()
+            || !matches!(settings.goal, None | Some(GoalSettings::RowMajor))
+        {
+            return Err(anyhow!(
+                "Boards that are not square can only start from the row_major goal."
+            ));
+        }
+        return Ok((0..width * height).collect());
+    }
+    let goal = settings.get_goal(width)?;
+    goal.check_complete()?;
+    (0..width * height)
+        .map(|i| goal.target().get(Pos::new(i % width, i / width)))
+        .collect()
+}
+
+pub fn run(settings: &Settings, on_layer: impl FnMut(usize, u64)) -> Result<ExploreReport> {
+    let (width, height) = settings
+        .board
+        .ok_or_else(|| anyhow!("Need a size or a board."))?;
+    let dir = settings
+        .work_dir
+        .as_ref()
+        .ok_or_else(|| anyhow!("Need a working directory."))?;
+    let start = start_cells(settings, width, height)?;
+    let topology = settings.topology.unwrap_or_default();
+    let mut explorer = Explorer::new_on(dir, width, height, &start, topology)?;
+    if let Some(chunk) = settings.chunk {
+        explorer.set_chunk(chunk);
+    }
+    explorer.run(settings.depth, on_layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::check::distances_from;
+    use crate::Goal;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("n_puzzle_test_explore_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_explore_small_boards() -> Result<()> {
+        let dir = temp_dir("small");
+        let mut explorer = Explorer::new(&dir, 2, 2, &[0, 1, 2, 3])?;
+        let report = explorer.run(None, |_, _| {})?;
+        assert_eq!(report.counts, vec![1, 2, 2, 2, 2, 2, 1]);
+        assert!(report.complete);
+        fs::remove_dir_all(&dir)?;
+
+        let mut explorer = Explorer::new(&dir, 3, 2, &[0, 1, 2, 3, 4, 5])?;
+        explorer.set_chunk(7);
+        let report = explorer.run(None, |_, _| {})?;
+        assert_eq!(report.states(), 360);
+        assert!(report.complete);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_explore_torus() -> Result<()> {
+        let dir = temp_dir("torus");
+        // A side of 2 wraps to the same neighbor, so the 2x2 torus is the 2x2 board
+        let report =
+            Explorer::new_on(&dir, 2, 2, &[0, 1, 2, 3], Topology::Torus)?.run(None, |_, _| {})?;
+        assert_eq!(report.states(), 12);
+        fs::remove_dir_all(&dir)?;
+        // An odd side lets every arrangement be reached, and an even side keeps the parity
+        let report = Explorer::new_on(&dir, 3, 2, &[0, 1, 2, 3, 4, 5], Topology::Torus)?
+            .run(None, |_, _| {})?;
+        assert_eq!(report.states(), 720);
+        assert!(report.to_string().contains("3x2 torus board"));
+        fs::remove_dir_all(&dir)?;
+        let start: Vec<usize> = (0..8).collect();
+        let report = Explorer::new_on(&dir, 4, 2, &start, Topology::Torus)?.run(None, |_, _| {})?;
+        assert_eq!(report.states(), 20_160);
+        assert!(Explorer::new(&dir, 4, 2, &start).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_explore_matches_memory_search() -> Result<()> {
+        let dir = temp_dir("memory");
+        let goal = Goal::snail(3);
+        let start: Vec<usize> = (0..9)
+            .map(|i| goal.target().get(Pos::new(i % 3, i / 3)))
+            .collect::<Result<_>>()?;
+        let mut explorer = Explorer::new(&dir, 3, 3, &start)?;
+        explorer.set_chunk(10_000);
+        let mut layers = Vec::new();
+        let report = explorer.run(None, |depth, count| layers.push((depth, count)))?;
+        let mut expected = Vec::new();
+        for (_, distance) in distances_from(&goal, None) {
+            if expected.len() <= distance {
+                expected.push(0);
+            }
+            expected[distance] += 1;
+        }
+        assert_eq!(report.counts, expected);
+        assert_eq!(report.states(), 181_440);
+        assert_eq!(layers.len(), expected.len());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_explore_resume() -> Result<()> {
+        let dir = temp_dir("resume");
+        let start = [0, 1, 2, 3, 4, 5];
+        let report = Explorer::new(&dir, 2, 3, &start)?.run(Some(5), |_, _| {})?;
+        assert_eq!(report.counts.len(), 6);
+        assert!(!report.complete);
+        assert_eq!(report.resumed_depth, None);
+
+        // Leftovers of an interrupted layer are removed
+        fs::write(dir.join("run-0.bin"), [0; 8])?;
+        fs::write(dir.join("layer-6.tmp"), [0; 4])?;
+        let mut layers = Vec::new();
+        let resumed =
+            Explorer::new(&dir, 2, 3, &start)?.run(None, |depth, _| layers.push(depth))?;
+        assert_eq!(resumed.resumed_depth, Some(5));
+        assert_eq!(resumed.counts[..6], report.counts[..]);
+        assert_eq!(resumed.states(), 360);
+        assert!(resumed.complete);
+        assert_eq!(layers, (0..resumed.counts.len()).collect::<Vec<_>>());
+        assert!(!dir.join("run-0.bin").exists());
+        let finished = Explorer::new(&dir, 2, 3, &start)?.run(None, |_, _| {})?;
+        assert_eq!(finished.resumed_depth, Some(resumed.counts.len() - 1));
+        assert_eq!(finished.counts, resumed.counts);
+
+        assert!(Explorer::new(&dir, 3, 2, &start).is_err());
+        assert!(Explorer::new(&dir, 2, 3, &[1, 0, 2, 3, 4, 5]).is_err());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_explore_invalid_board() {
+        let dir = temp_dir("invalid");
+        assert!(Explorer::new(&dir, 1, 4, &[0, 1, 2, 3]).is_err());
+        assert!(Explorer::new(&dir, 5, 4, &(0..20).collect::<Vec<_>>()).is_err());
+        assert!(Explorer::new(&dir, 2, 2, &[0, 1, 1, 3]).is_err());
+        assert!(!dir.exists());
+    }
+}
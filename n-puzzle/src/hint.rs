@@ -0,0 +1,360 @@
+use crate::args::Settings;
+use crate::{Algorithm, Goal, Heuristic, Move, Oracle, Puzzle, Solver};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+/// Suggested next moves and the number of moves left to the goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    /// Equally good next moves, empty when the goal is reached
+    pub moves: Vec<Move>,
+    pub distance: usize,
+    /// Whether the hint was read from the solutions of previous hints, without a search
+    pub cached: bool,
+    /// Whether `distance` is the length of the shortest solution
+    pub optimal: bool,
+}
+
+/// Gives hints for successive states of the same game.
+///
+/// 3x3 boards are answered by the distance oracle with every optimal move, unless the goal has wildcards.
+/// Larger boards are solved, and the solutions are reused as long as the player follows them.
+/// With an optimal algorithm and an admissible heuristic, the neighbors are solved too,
+/// so that every equally optimal move is given; otherwise only the next move of the solution is.
+pub struct Hinter {
+    algorithm: Algorithm,
+    heuristic: Heuristic,
+    timeout: Option<u64>,
+    goal: Option<Goal>,
+    /// Distances of the 3x3 boards to the goal, built at the first hint unless one is given
+    oracle: Option<Arc<Oracle>>,
+    /// Moves left and next move of every state on the solutions found so far
+    solved: HashMap<Puzzle, (usize, Option<Move>)>,
+}
+
+impl Hinter {
+    pub fn new(algorithm: Algorithm, heuristic: Heuristic, timeout: Option<u64>) -> Self {
+        Self {
+            algorithm,
+            heuristic,
+            timeout,
+            goal: None,
+            oracle: None,
+            solved: HashMap::new(),
+        }
+    }
+
+    /// Give hints towards the given goal instead of the snail goal
+    pub fn set_goal(&mut self, goal: Goal) {
+        self.goal = Some(goal);
+        self.oracle = None;
+        self.solved.clear();
+    }
+
+    /// Answer 3x3 boards with an oracle of the goal, e.g. one read from a cache file
+    pub fn set_oracle(&mut self, oracle: Arc<Oracle>) {
+        self.oracle = Some(oracle);
+    }
+
+    /// Moves left and next move of `puzzle`, searched unless a previous solution went through it
+    fn solve(&mut self, puzzle: &Puzzle, goal: &Goal) -> Result<(usize, Option<Move>)> {
+        if let Some(&solved) = self.solved.get(puzzle) {
+            return Ok(solved);
+        }
+        let mut solver = Solver::new(
+            self.algorithm,
+            self.heuristic,
+            puzzle.clone(),
+            self.timeout,
+            false,
+        );
+        solver.set_goal(goal.clone());
+        let path = solver.solve()?.path;
+        let mut state = puzzle.clone();
+        for (i, m) in path.iter().enumerate() {
+            self.solved
+                .entry(state.clone())
+                .or_insert((path.len() - i, Some(*m)));
+            state.move_blank(*m)?;
+        }
+        self.solved.entry(state).or_insert((0, None));
+        Ok((path.len(), path.first().copied()))
+    }
+
+    /// Whether the solutions found for the puzzle are the shortest ones
+    fn is_optimal(&self, puzzle: &Puzzle) -> bool {
+        self.algorithm.is_optimal()
+            && (!self.algorithm.is_heuristic()
+                || self.heuristic.is_admissible(puzzle.get_topology()))
+    }
+
+    pub fn hint(&mut self, puzzle: &Puzzle) -> Result<Hint> {
+        let goal = match &self.goal {
+            Some(goal) => goal.clone(),
+            None => Goal::snail(puzzle.get_size()),
+        };
+        goal.check_size(puzzle)?;
+        if !puzzle.is_solvable_to(&goal)? {
+            return Err(anyhow!("This puzzle is unsolvable."));
+        }
+        if puzzle.get_size() == 3 && !goal.is_partial() {
+            let oracle = match &self.oracle {
+                Some(oracle) => oracle.clone(),
+                None => Oracle::get(&goal)?,
+            };
+            self.oracle = Some(oracle.clone());
+            let answer = oracle.query(puzzle)?;
+            return Ok(Hint {
+                moves: answer.moves,
+                distance: answer.distance,
+                cached: false,
+                optimal: true,
+            });
+        }
+        let optimal = self.is_optimal(puzzle);
+        let neighbors: Vec<(Move, Puzzle)> = Move::list()
+            .into_iter()
+            .filter_map(|m| {
+                let mut next = puzzle.clone();
+                next.move_blank(m).ok()?;
+                Some((m, next))
+            })
+            .collect();
+        let cached = self.solved.contains_key(puzzle)
+            && (!optimal
+                || neighbors
+                    .iter()
+                    .all(|(_, next)| self.solved.contains_key(next)));
+        let (distance, next_move) = self.solve(puzzle, &goal)?;
+        // Only optimal distances tell which other moves are as short as the one of the solution
+        let moves = if optimal && distance > 0 {
+            let mut moves = Vec::new();
+            for (m, next) in neighbors {
+                if self.solve(&next, &goal)?.0 + 1 == distance {
+                    moves.push(m);
+                }
+            }
+            moves
+        } else {
+            next_move.into_iter().collect()
+        };
+        Ok(Hint {
+            moves,
+            distance,
+            cached,
+            optimal,
+        })
+    }
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.moves.is_empty() {
+            return writeln!(f, "Solved.");
+        }
+        let moves: Vec<String> = self.moves.iter().map(|m| format!("{:?}", m)).collect();
+        if self.optimal {
+            writeln!(f, "Distance: {}", self.distance)?;
+        } else {
+            writeln!(
+                f,
+                "Distance: {} (not optimal: use astar or ida_star with an admissible heuristic)",
+                self.distance
+            )?;
+        }
+        writeln!(f, "Next move: {}", moves.join(" or "))
+    }
+}
+
+fn parse_move(text: &str) -> Result<Move> {
+    match text.trim().to_lowercase().as_str() {
+        "u" | "up" => Ok(Move::Up),
+        "d" | "down" => Ok(Move::Down),
+        "l" | "left" => Ok(Move::Left),
+        "r" | "right" => Ok(Move::Right),
+        _ => Err(anyhow!(
+            "Not a valid move: {}. Use up, down, left or right",
+            text.trim()
+        )),
+    }
+}
+
+pub(crate) fn new_hinter(settings: &Settings, puzzle: &Puzzle) -> Result<Hinter> {
+    let mut hinter = Hinter::new(
+        settings.algorithm.unwrap_or(Algorithm::IdaStar),
+        settings.heuristic,
+        settings.timeout,
+    );
+    let goal = settings.get_goal(puzzle.get_size())?;
+    hinter.set_goal(goal.clone());
+    if puzzle.get_size() == 3 && !goal.is_partial() {
+        hinter.set_oracle(crate::get_oracle(settings, &goal)?);
+    }
+    Ok(hinter)
+}
+
+/// Print a hint after every move read from `input` until the goal is reached.
+/// An empty line plays the first suggested move.
+pub(crate) fn play(
+    settings: &Settings,
+    mut puzzle: Puzzle,
+    input: impl BufRead,
+    output: &mut impl Write,
+) -> Result<Hint> {
+    let mut hinter = new_hinter(settings, &puzzle)?;
+    let mut hint = hinter.hint(&puzzle)?;
+    write!(output, "{}{}", puzzle, hint)?;
+    let mut lines = input.lines();
+    while !hint.moves.is_empty() {
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        let m = match line.trim() {
+            "" => hint.moves[0],
+            text => match parse_move(text) {
+                Ok(m) => m,
+                Err(e) => {
+                    writeln!(output, "{}", e)?;
+                    continue;
+                }
+            },
+        };
+        if puzzle.move_blank(m).is_err() {
+            writeln!(output, "Cannot move {:?}.", m)?;
+            continue;
+        }
+        hint = hinter.hint(&puzzle)?;
+        write!(output, "{}{}", puzzle, hint)?;
+    }
+    Ok(hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_oracle() -> Result<()> {
+        let mut hinter = Hinter::new(Algorithm::AStar, Heuristic::Manhattan, None);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 6, 4], vec![7, 0, 5]])?;
+        let hint = hinter.hint(&puzzle)?;
+        assert_eq!(hint.moves, vec![Move::Up]);
+        assert_eq!(hint.distance, 1);
+        assert!(hint.optimal);
+        assert!(!hint.cached);
+        let hint = hinter.hint(&Puzzle::new_answer(3))?;
+        assert_eq!(hint.moves, vec![]);
+        assert_eq!(hint.distance, 0);
+
+        // An oracle given by the caller is used as is
+        let mut hinter = Hinter::new(Algorithm::AStar, Heuristic::Manhattan, None);
+        let oracle = Arc::new(Oracle::build(&Goal::snail(3))?);
+        hinter.set_oracle(oracle.clone());
+        hinter.hint(&puzzle)?;
+        assert!(Arc::ptr_eq(hinter.oracle.as_ref().unwrap(), &oracle));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_cached() -> Result<()> {
+        let mut hinter = Hinter::new(Algorithm::AStar, Heuristic::Manhattan, None);
+        let mut puzzle = Puzzle::new_from_state(vec![
+            vec![1, 2, 3, 4],
+            vec![12, 13, 14, 5],
+            vec![11, 15, 6, 0],
+            vec![10, 9, 8, 7],
+        ])?;
+        let hint = hinter.hint(&puzzle)?;
+        assert!(!hint.cached);
+        assert!(!hint.optimal);
+        assert_eq!(hint.distance, 2);
+        puzzle.move_blank(hint.moves[0])?;
+        let hint = hinter.hint(&puzzle)?;
+        assert!(hint.cached);
+        assert_eq!(hint.distance, 1);
+        puzzle.move_blank(hint.moves[0])?;
+        assert!(puzzle.is_final_state());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_optimal() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![
+            vec![1, 2, 3, 4],
+            vec![12, 13, 0, 5],
+            vec![11, 15, 14, 6],
+            vec![10, 9, 8, 7],
+        ])?;
+        let mut hinter = Hinter::new(Algorithm::IdaStar, Heuristic::InversionDistance, None);
+        let hint = hinter.hint(&puzzle)?;
+        assert!(hint.optimal);
+        assert_eq!(hint.distance, 2);
+        let mut hinter = Hinter::new(Algorithm::Greedy, Heuristic::InversionDistance, None);
+        let hint = hinter.hint(&puzzle)?;
+        assert!(!hint.optimal);
+        assert!(hint.to_string().contains("not optimal"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_every_move() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![
+            vec![1, 2, 3, 4],
+            vec![0, 11, 14, 5],
+            vec![13, 12, 15, 6],
+            vec![10, 9, 8, 7],
+        ])?;
+        let mut hinter = Hinter::new(Algorithm::IdaStar, Heuristic::InversionDistance, None);
+        let hint = hinter.hint(&puzzle)?;
+        assert_eq!(hint.moves, vec![Move::Down, Move::Right]);
+        assert_eq!(hint.distance, 6);
+        assert!(!hint.cached);
+        for m in hint.moves {
+            let mut next = puzzle.clone();
+            next.move_blank(m)?;
+            assert_eq!(hinter.hint(&next)?.distance, 5);
+        }
+        // Every neighbor of the first board has been solved
+        assert!(hinter.hint(&puzzle)?.cached);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_move() -> Result<()> {
+        assert_eq!(parse_move("u")?, Move::Up);
+        assert_eq!(parse_move(" Right ")?, Move::Right);
+        assert!(parse_move("up left").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_play() -> Result<()> {
+        let mut settings = Settings::new_default();
+        settings.heuristic = Heuristic::Manhattan;
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 4, 0], vec![7, 6, 5]])?;
+        let mut output = Vec::new();
+        let hint = play(
+            &settings,
+            puzzle,
+            "x\nright\nleft\n\n".as_bytes(),
+            &mut output,
+        )?;
+        assert_eq!(hint.distance, 0);
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("Not a valid move: x."));
+        assert!(output.contains("Cannot move Right."));
+        assert!(output.ends_with("Solved.\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hint_unsolvable() -> Result<()> {
+        let mut hinter = Hinter::new(Algorithm::AStar, Heuristic::Manhattan, None);
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert!(hinter.hint(&puzzle).is_err());
+        Ok(())
+    }
+}
@@ -1,43 +1,308 @@
 mod algorithm;
 mod args;
+mod batch;
+mod bench;
+mod check;
+mod explore;
+mod hint;
+mod korf;
 mod n_puzzle;
+mod oracle;
 
-pub use algorithm::{Algorithm, Heuristic};
-pub use n_puzzle::{Move, Pos, Puzzle, PuzzleSettings};
+pub use algorithm::{
+    optimize_path, Algorithm, Heuristic, OpenList, OpenOptions, Optimization, Output, Phase,
+    Replacement, SearchStats, Solutions, Solver, TableStats, TieBreak, Timeout,
+};
+pub use batch::{BatchResult, BatchStatus, BatchSummary};
+pub use bench::{BenchRecord, BenchReport};
+pub use check::{CheckReport, HeuristicCheck, Inadmissible, Inconsistent};
+pub use explore::{ExploreReport, Explorer};
+pub use hint::{Hint, Hinter};
+pub use korf::{KorfInstance, KorfResult, KorfSummary};
+pub use n_puzzle::{
+    Goal, GoalSettings, Move, Pos, Puzzle, PuzzleSettings, States, Topology, ZobristBuildHasher,
+    ZobristHasher,
+};
+pub use oracle::{Oracle, OracleAnswer};
 
-use algorithm::{Output, Solver};
-use anyhow::Result;
-use args::{get_args, parse_args};
+use anyhow::{anyhow, Result};
+use args::{get_args, parse_args, Command, Settings};
+use std::fs;
 
 const MAX_PUZZLE_SIZE: usize = 100;
 
+/// Function of the library that runs each command
+fn runner(command: Command) -> (&'static str, &'static str) {
+    match command {
+        Command::Solve => ("solve", "run"),
+        Command::Batch => ("batch", "run_batch"),
+        Command::Bench => ("bench", "run_bench"),
+        Command::Korf => ("korf", "run_korf"),
+        Command::Check => ("check", "run_check"),
+        Command::Oracle => ("oracle", "run_oracle"),
+        Command::Hint => ("hint", "run_hint"),
+        Command::Explore => ("explore", "run_explore"),
+    }
+}
+
+/// Parse the arguments of `command`, failing with the function to use for another command
+fn parse_command(args: Vec<String>, command: Command) -> Result<Option<Settings>> {
+    let settings = match parse_args(args)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    if settings.command != command {
+        let (name, function) = runner(command);
+        let (other_name, other_function) = runner(settings.command);
+        return Err(anyhow!(
+            "{} runs the {} command. Use {} for the {} command.",
+            function,
+            name,
+            other_function,
+            other_name
+        ));
+    }
+    Ok(Some(settings))
+}
+
 pub fn run(args: Vec<String>) -> Result<Option<(Solver, Output)>> {
-    // Parse arguments
-    let settings = match parse_args(args) {
-        Ok(Some(settings)) => settings,
-        Ok(_) => return Ok(None),
-        Err(e) => return Err(e),
+    let settings = match parse_command(args, Command::Solve)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    solve(settings).map(Some)
+}
+
+pub fn run_batch(args: Vec<String>) -> Result<Option<BatchSummary>> {
+    let settings = match parse_command(args, Command::Batch)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    batch::solve_all(&settings, |_| {}).map(Some)
+}
+
+pub fn run_bench(args: Vec<String>) -> Result<Option<BenchReport>> {
+    let settings = match parse_command(args, Command::Bench)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    bench(&settings).map(Some)
+}
+
+pub fn run_korf(args: Vec<String>) -> Result<Option<KorfSummary>> {
+    let settings = match parse_command(args, Command::Korf)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    korf::run(&settings, |_| {}).map(Some)
+}
+
+pub fn run_check(args: Vec<String>) -> Result<Option<CheckReport>> {
+    let settings = match parse_command(args, Command::Check)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    check::run(&settings).map(Some)
+}
+
+pub fn run_explore(args: Vec<String>) -> Result<Option<ExploreReport>> {
+    let settings = match parse_command(args, Command::Explore)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    explore::run(&settings, |_, _| {}).map(Some)
+}
+
+pub fn run_oracle(args: Vec<String>) -> Result<Option<OracleAnswer>> {
+    let settings = match parse_command(args, Command::Oracle)? {
+        Some(settings) => settings,
+        None => return Ok(None),
+    };
+    query_oracle(settings).map(Some)
+}
+
+pub fn run_hint(args: Vec<String>) -> Result<Option<Hint>> {
+    let settings = match parse_command(args, Command::Hint)? {
+        Some(settings) => settings,
+        None => return Ok(None),
     };
+    let puzzle = Puzzle::new(settings.puzzle_settings.clone())?;
+    hint::new_hinter(&settings, &puzzle)?
+        .hint(&puzzle)
+        .map(Some)
+}
+
+/// Oracle of the goal, read from the cache file when one is given
+fn get_oracle(settings: &Settings, goal: &Goal) -> Result<std::sync::Arc<Oracle>> {
+    match &settings.cache_path {
+        Some(cache_path) => Oracle::get_cached(goal, cache_path),
+        None => Oracle::get(goal),
+    }
+}
+
+fn query_oracle(settings: Settings) -> Result<OracleAnswer> {
+    let puzzle = Puzzle::new(settings.puzzle_settings.clone())?;
+    let goal = settings.get_goal(puzzle.get_size())?;
+    get_oracle(&settings, &goal)?.query(&puzzle)
+}
+
+fn bench(settings: &Settings) -> Result<BenchReport> {
+    let report = bench::run(settings)?;
+    if let Some(csv_path) = &settings.csv_path {
+        fs::write(csv_path, report.to_csv())?;
+    }
+    Ok(report)
+}
+
+/// Solver of the puzzle given by the settings, or generated from them
+fn new_solver(settings: &Settings) -> Result<Solver> {
     // Generate puzzle
-    let puzzle = Puzzle::new(settings.puzzle_settings)?;
+    let topology = settings.topology.unwrap_or_default();
+    let mut puzzle = match &settings.puzzle_settings {
+        PuzzleSettings::Size(size) => {
+            Puzzle::generate_solvable_on(*size, &settings.get_goal(*size)?, topology)?
+        }
+        PuzzleSettings::TextPath(text_path) => {
+            Puzzle::new(PuzzleSettings::TextPath(text_path.clone()))?
+        }
+    };
+    puzzle.set_topology(topology);
+    let mut goal = settings.get_goal(puzzle.get_size())?;
+    if settings.heuristic == Heuristic::Exact {
+        goal = goal.clone().with_oracle(get_oracle(settings, &goal)?);
+    }
     // Solve puzzle
-    let solver = Solver::new(
+    let mut solver = Solver::new(
         settings.algorithm.unwrap(),
         settings.heuristic,
         puzzle,
         settings.timeout,
         settings.verbose,
     );
-    let output = solver.solve()?;
-    Ok(Some((solver, output)))
+    solver.set_goal(goal);
+    if let Some(core_size) = settings.core_size {
+        solver.set_core_size(core_size);
+    }
+    if let Some(threads) = settings.threads {
+        solver.set_threads(threads);
+    }
+    if let Some(node_budget) = settings.node_budget {
+        solver.set_node_budget(node_budget);
+    }
+    if let Some(beam_width) = settings.beam_width {
+        solver.set_beam_width(beam_width);
+    }
+    solver.set_open_options(settings.open_options());
+    if let Some(table_size) = settings.table_size {
+        let policy = settings.table_policy.unwrap_or(Replacement::Depth);
+        solver.set_transposition_table(table_size, policy);
+    }
+    Ok(solver)
+}
+
+fn solve(settings: Settings) -> Result<(Solver, Output)> {
+    let mut solver = match &settings.resume_path {
+        Some(resume_path) => {
+            Solver::from_checkpoint(resume_path, settings.timeout, settings.verbose)?
+        }
+        None => new_solver(&settings)?,
+    };
+    // A resumed search keeps saving its state to its checkpoint, unless another file is given
+    if let Some(path) = settings
+        .checkpoint_path
+        .as_ref()
+        .or(settings.resume_path.as_ref())
+    {
+        let interval = settings
+            .checkpoint_interval
+            .unwrap_or(algorithm::DEFAULT_CHECKPOINT_INTERVAL);
+        solver.set_checkpoint(path, interval);
+    }
+    let mut output = solver.solve()?;
+    if settings.optimize {
+        let window = settings.window.unwrap_or(algorithm::DEFAULT_WINDOW);
+        // The windows are searched within the time left by the search
+        let remaining = settings
+            .timeout
+            .map(|t| t.saturating_sub(output.elapsed_time as u64));
+        let optimization = optimize_path(solver.start_state(), &output.path, window, remaining)?;
+        output.path = optimization.path.clone();
+        output.optimization = Some(optimization);
+    }
+    if settings.count_solutions || settings.solutions.is_some() {
+        output.solutions = Some(solver.optimal_solutions(output.path.len(), settings.solutions)?);
+    }
+    // Export images
+    if let Some(svg_path) = settings.svg_path {
+        fs::write(svg_path, solver.start_state().to_svg())?;
+    }
+    if let Some(html_path) = settings.html_path {
+        fs::write(html_path, output.to_html(solver.start_state().clone())?)?;
+    }
+    Ok((solver, output))
 }
 
 pub fn cui_run() -> Result<()> {
     let args = get_args();
-    let (solver, output) = match run(args)? {
-        Some((solver, output)) => (solver, output),
+    let settings = match parse_args(args)? {
+        Some(settings) => settings,
         None => return Ok(()),
     };
-    solver.put_result(output)?;
+    match settings.command {
+        Command::Solve => {
+            let (solver, output) = solve(settings)?;
+            solver.put_result(output)?;
+        }
+        Command::Batch => {
+            let summary = batch::solve_all(&settings, |result| println!("{}", result))?;
+            print!("{}", summary);
+        }
+        Command::Bench => {
+            let report = bench(&settings)?;
+            print!("{}", report.to_table());
+        }
+        Command::Oracle => {
+            let answer = query_oracle(settings)?;
+            println!("Distance: {}", answer.distance);
+            let moves: Vec<String> = answer.moves.iter().map(|m| format!("{:?}", m)).collect();
+            println!("Optimal first moves: {}", moves.join(" "));
+        }
+        Command::Hint => {
+            let puzzle = Puzzle::new(settings.puzzle_settings.clone())?;
+            if settings.interactive {
+                let stdin = std::io::stdin();
+                hint::play(&settings, puzzle, stdin.lock(), &mut std::io::stdout())?;
+            } else {
+                let hint = hint::new_hinter(&settings, &puzzle)?.hint(&puzzle)?;
+                print!("{}", hint);
+            }
+        }
+        Command::Check => {
+            let report = check::run(&settings)?;
+            print!("{}", report);
+            if report.failures() > 0 {
+                return Err(anyhow!(
+                    "{} heuristics are not admissible or not consistent.",
+                    report.failures()
+                ));
+            }
+        }
+        Command::Explore => {
+            let report = explore::run(&settings, |depth, count| {
+                println!("Depth {}: {}", depth, count)
+            })?;
+            print!("{}", report);
+        }
+        Command::Korf => {
+            let summary = korf::run(&settings, |result| println!("{}", result))?;
+            print!("{}", summary);
+            if summary.mismatches() > 0 {
+                return Err(anyhow!(
+                    "{} instances were not solved with the optimal number of moves.",
+                    summary.mismatches()
+                ));
+            }
+        }
+    }
     Ok(())
 }
@@ -1,14 +1,22 @@
 mod generator;
+mod goal;
 mod parser;
 mod pos;
+mod rank;
 mod solvable;
+mod svg;
+mod zobrist;
 
+pub use goal::{Goal, GoalSettings};
 pub use pos::Pos;
+pub(crate) use rank::rank_cells;
+pub use rank::States;
+pub use zobrist::{ZobristBuildHasher, ZobristHasher};
 
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PuzzleSettings {
     Size(usize),
     TextPath(String),
@@ -26,16 +34,60 @@ impl Move {
     pub fn list() -> Vec<Move> {
         vec![Move::Up, Move::Down, Move::Left, Move::Right]
     }
+
+    /// Move that undoes this one
+    pub fn opposite(&self) -> Move {
+        match self {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Left => Move::Right,
+            Move::Right => Move::Left,
+        }
+    }
+}
+
+/// How the edges of the board are connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    /// The blank stops at the edges
+    #[default]
+    Plane,
+    /// The blank wraps from one edge to the opposite edge
+    Torus,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Topology::Plane => "plane",
+            Topology::Torus => "torus",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Puzzle {
     size: usize,
     state: Vec<Vec<usize>>,
     blank_pos: Pos,
+    topology: Topology,
+    /// Zobrist key of `state`, which is also the hash of the puzzle
+    zobrist: u64,
 }
 
 impl Puzzle {
+    fn build(size: usize, state: Vec<Vec<usize>>, blank_pos: Pos) -> Self {
+        let zobrist = zobrist::state_key(&state);
+        Self {
+            size,
+            state,
+            blank_pos,
+            topology: Topology::Plane,
+            zobrist,
+        }
+    }
+
     /// Generate a new puzzle with PuzzleSettings
     pub fn new(settings: PuzzleSettings) -> Result<Self> {
         match settings {
@@ -55,11 +107,7 @@ impl Puzzle {
                 }
             }
         }
-        let puzzle = Self {
-            size,
-            state,
-            blank_pos,
-        };
+        let puzzle = Self::build(size, state, blank_pos);
         if !puzzle.check_state() {
             return Err(anyhow!("Invalid state"));
         }
@@ -103,11 +151,7 @@ impl Puzzle {
             }
         }
         state[pos.y][pos.x] = 0;
-        Self {
-            size,
-            state,
-            blank_pos: pos,
-        }
+        Self::build(size, state, pos)
     }
 
     pub fn generate_answer_pos_map(size: usize) -> HashMap<usize, Pos> {
@@ -194,6 +238,9 @@ impl Puzzle {
         if pos.x >= self.size || pos.y >= self.size {
             return Err(anyhow!("Index out of bounds: ({}, {})", pos.x, pos.y));
         }
+        let index = pos.y * self.size + pos.x;
+        self.zobrist ^= zobrist::tile_key(index, self.state[pos.y][pos.x]);
+        self.zobrist ^= zobrist::tile_key(index, val);
         self.state[pos.y][pos.x] = val;
         if val == 0 {
             self.blank_pos = pos;
@@ -222,6 +269,15 @@ impl Puzzle {
         self.blank_pos
     }
 
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Let the blank wrap at the edges of the board, or stop there
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+    }
+
     /// Swap the values at the given positions
     pub fn swap(&mut self, pos1: Pos, pos2: Pos) -> Result<()> {
         if pos1.x >= self.size || pos1.y >= self.size || pos2.x >= self.size || pos2.y >= self.size
@@ -236,6 +292,9 @@ impl Puzzle {
         }
         let val1 = self.get(pos1)?;
         let val2 = self.get(pos2)?;
+        let (index1, index2) = (pos1.y * self.size + pos1.x, pos2.y * self.size + pos2.x);
+        self.zobrist ^= zobrist::tile_key(index1, val1) ^ zobrist::tile_key(index2, val1);
+        self.zobrist ^= zobrist::tile_key(index2, val2) ^ zobrist::tile_key(index1, val2);
         self.state[pos1.y][pos1.x] = val2;
         self.state[pos2.y][pos2.x] = val1;
         if val1 == 0 {
@@ -246,36 +305,26 @@ impl Puzzle {
         Ok(())
     }
 
-    /// Move the blank position
+    /// Move the blank position, wrapping at the edges of a torus board
     pub fn move_blank(&mut self, mv: Move) -> Result<()> {
         let pos = self.blank_pos;
-        match mv {
-            Move::Up => {
-                if pos.y == 0 {
-                    return Err(anyhow!("Cannot move up"));
-                }
-                self.swap(pos, pos - Pos::new(0, 1))?;
-            }
-            Move::Down => {
-                if pos.y == self.size - 1 {
-                    return Err(anyhow!("Cannot move down"));
-                }
-                self.swap(pos, pos + Pos::new(0, 1))?;
-            }
-            Move::Left => {
-                if pos.x == 0 {
-                    return Err(anyhow!("Cannot move left"));
-                }
-                self.swap(pos, pos - Pos::new(1, 0))?;
-            }
-            Move::Right => {
-                if pos.x == self.size - 1 {
-                    return Err(anyhow!("Cannot move right"));
-                }
-                self.swap(pos, pos + Pos::new(1, 0))?;
-            }
-        }
-        Ok(())
+        let last = self.size - 1;
+        let torus = self.topology == Topology::Torus;
+        let target = match mv {
+            Move::Up if pos.y > 0 => pos - Pos::new(0, 1),
+            Move::Up if torus => Pos::new(pos.x, last),
+            Move::Up => return Err(anyhow!("Cannot move up")),
+            Move::Down if pos.y < last => pos + Pos::new(0, 1),
+            Move::Down if torus => Pos::new(pos.x, 0),
+            Move::Down => return Err(anyhow!("Cannot move down")),
+            Move::Left if pos.x > 0 => pos - Pos::new(1, 0),
+            Move::Left if torus => Pos::new(last, pos.y),
+            Move::Left => return Err(anyhow!("Cannot move left")),
+            Move::Right if pos.x < last => pos + Pos::new(1, 0),
+            Move::Right if torus => Pos::new(0, pos.y),
+            Move::Right => return Err(anyhow!("Cannot move right")),
+        };
+        self.swap(pos, target)
     }
 
     pub fn generate_arrange_order_answer_map(size: usize, zero: bool) -> HashMap<usize, usize> {
@@ -389,6 +438,8 @@ mod tests {
             size: 3,
             state: vec![vec![0; 3]; 3],
             blank_pos: Pos::new(2, 2),
+            topology: Topology::Plane,
+            zobrist: 0,
         };
         puzzle.set(Pos::new(0, 0), 1).unwrap();
         assert_eq!(puzzle.state[0][0], 1);
@@ -446,6 +497,32 @@ mod tests {
         assert!(puzzle.move_blank(Move::Left).is_err());
     }
 
+    #[test]
+    fn test_move_blank_torus() {
+        let mut puzzle = Puzzle::new_answer(3);
+        puzzle.set_topology(Topology::Torus);
+        puzzle.move_blank(Move::Up).unwrap();
+        puzzle.move_blank(Move::Up).unwrap();
+        assert_eq!(
+            puzzle.state,
+            vec![vec![1, 6, 3], vec![8, 2, 4], vec![7, 0, 5]]
+        );
+        puzzle.move_blank(Move::Right).unwrap();
+        puzzle.move_blank(Move::Right).unwrap();
+        assert_eq!(
+            puzzle.state,
+            vec![vec![1, 6, 3], vec![8, 2, 4], vec![0, 5, 7]]
+        );
+        assert_eq!(puzzle.blank_pos, Pos::new(0, 2));
+        puzzle.move_blank(Move::Left).unwrap();
+        puzzle.move_blank(Move::Down).unwrap();
+        assert_eq!(
+            puzzle.state,
+            vec![vec![1, 6, 0], vec![8, 2, 4], vec![7, 5, 3]]
+        );
+        assert_eq!(puzzle.blank_pos, Pos::new(2, 0));
+    }
+
     #[test]
     fn test_display() {
         let puzzle = Puzzle::new_answer(3);
@@ -1,9 +1,14 @@
-use super::{Pos, Puzzle};
+use super::{Goal, Pos, Puzzle, Topology};
 use anyhow::{anyhow, Result};
 use rand::seq::SliceRandom;
+use rand::Rng;
 
 impl Puzzle {
     pub(super) fn generate(size: usize) -> Result<Self> {
+        Self::generate_with_rng(size, &mut rand::thread_rng())
+    }
+
+    fn generate_with_rng<R: Rng>(size: usize, rng: &mut R) -> Result<Self> {
         if size <= 1 {
             return Err(anyhow!("invalid size selected"));
         }
@@ -11,8 +16,7 @@ impl Puzzle {
         let mut state = vec![vec![0; size]; size];
         let mut blank_pos = Pos::new(0, 0);
         let mut rand_state: Vec<usize> = (0..=size * size - 1).collect();
-        let mut rng = rand::thread_rng();
-        rand_state.shuffle(&mut rng);
+        rand_state.shuffle(rng);
 
         for i in 0..size {
             for j in 0..size {
@@ -23,17 +27,39 @@ impl Puzzle {
                 }
             }
         }
-        Ok(Self {
-            size,
-            state,
-            blank_pos,
-        })
+        Ok(Self::build(size, state, blank_pos))
     }
 
     pub fn generate_solvable(size: usize) -> Result<Self> {
+        Self::generate_solvable_to(size, &Goal::snail(size))
+    }
+
+    /// Generate a puzzle that can reach the given goal
+    pub fn generate_solvable_to(size: usize, goal: &Goal) -> Result<Self> {
+        Self::generate_solvable_on(size, goal, Topology::Plane)
+    }
+
+    /// Generate a puzzle of the given topology that can reach the given goal
+    pub fn generate_solvable_on(size: usize, goal: &Goal, topology: Topology) -> Result<Self> {
         let mut puzzle = Self::generate(size)?;
-        while !puzzle.is_solvable()? {
+        puzzle.topology = topology;
+        while !puzzle.is_solvable_to(goal)? {
             puzzle = Self::generate(size)?;
+            puzzle.topology = topology;
+        }
+        Ok(puzzle)
+    }
+
+    /// Generate a puzzle that can reach the given goal from the given random number generator,
+    /// so that seeded generators give reproducible puzzles
+    pub fn generate_solvable_with_rng<R: Rng>(
+        size: usize,
+        goal: &Goal,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let mut puzzle = Self::generate_with_rng(size, rng)?;
+        while !puzzle.is_solvable_to(goal)? {
+            puzzle = Self::generate_with_rng(size, rng)?;
         }
         Ok(puzzle)
     }
@@ -69,4 +95,25 @@ mod tests {
         assert!(result);
         assert!(puzzle.is_solvable().unwrap());
     }
+
+    #[test]
+    fn test_generate_solvable_seeded() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let goal = Goal::snail(4);
+        let puzzle1 =
+            Puzzle::generate_solvable_with_rng(4, &goal, &mut StdRng::seed_from_u64(42)).unwrap();
+        let puzzle2 =
+            Puzzle::generate_solvable_with_rng(4, &goal, &mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(puzzle1, puzzle2);
+        assert!(puzzle1.is_solvable().unwrap());
+    }
+
+    #[test]
+    fn test_generate_solvable_to_row_major() {
+        let goal = Goal::row_major(3);
+        let puzzle = Puzzle::generate_solvable_to(3, &goal).unwrap();
+        assert!(puzzle.is_solvable_to(&goal).unwrap());
+    }
 }
@@ -0,0 +1,259 @@
+use super::{Pos, Puzzle};
+use crate::Oracle;
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoalSettings {
+    /// Tiles arranged in a spiral with the blank at the end, e.g. `1 2 3 / 8 0 4 / 7 6 5`
+    Snail,
+    /// Blank first and tiles in reading order, e.g. `0 1 2 / 3 4 5 / 6 7 8`
+    RowMajor,
+}
+
+impl std::fmt::Display for GoalSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            GoalSettings::Snail => "snail",
+            GoalSettings::RowMajor => "row_major",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Target state of a search, with the goal position of every value
+#[derive(Debug, Clone)]
+pub struct Goal {
+    target: Puzzle,
+    positions: Vec<Pos>,
+    /// Whether each value has to reach its goal position, false for wildcard values
+    fixed: Vec<bool>,
+    /// Exact distances to the target, resolved once for the exact heuristic
+    oracle: Option<Arc<Oracle>>,
+}
+
+impl PartialEq for Goal {
+    fn eq(&self, other: &Self) -> bool {
+        self.target == other.target && self.fixed == other.fixed
+    }
+}
+
+impl Goal {
+    pub fn new(settings: GoalSettings, size: usize) -> Self {
+        match settings {
+            GoalSettings::Snail => Self::snail(size),
+            GoalSettings::RowMajor => Self::row_major(size),
+        }
+    }
+
+    pub fn snail(size: usize) -> Self {
+        Self::from_puzzle(Puzzle::new_answer(size))
+    }
+
+    pub fn row_major(size: usize) -> Self {
+        let state = (0..size)
+            .map(|y| (0..size).map(|x| y * size + x).collect())
+            .collect();
+        Self::from_puzzle(Puzzle::build(size, state, Pos::new(0, 0)))
+    }
+
+    pub fn from_puzzle(target: Puzzle) -> Self {
+        let size = target.size;
+        let mut positions = vec![Pos::new(0, 0); size * size];
+        for y in 0..size {
+            for x in 0..size {
+                positions[target.state[y][x]] = Pos::new(x, y);
+            }
+        }
+        let fixed = vec![true; size * size];
+        Self {
+            target,
+            positions,
+            fixed,
+            oracle: None,
+        }
+    }
+
+    /// Goal where only the given values matter, `None` being a wildcard cell.
+    /// The values left out are placed on the wildcard cells in increasing order.
+    pub fn from_cells(size: usize, cells: &[Option<usize>]) -> Result<Self> {
+        if cells.len() != size * size {
+            return Err(anyhow!(
+                "Number of elements does not match puzzle size: {}.",
+                size
+            ));
+        }
+        let mut fixed = vec![false; size * size];
+        for value in cells.iter().flatten() {
+            if *value >= size * size || fixed[*value] {
+                return Err(anyhow!("Invalid goal format."));
+            }
+            fixed[*value] = true;
+        }
+        let mut wildcards = (0..size * size).filter(|&value| !fixed[value]);
+        let state = cells
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|cell| cell.unwrap_or_else(|| wildcards.next().unwrap()))
+                    .collect()
+            })
+            .collect();
+        let mut goal = Self::from_puzzle(Puzzle::new_from_state(state)?);
+        goal.fixed = fixed;
+        Ok(goal)
+    }
+
+    /// Parse a goal file in the puzzle format, where `*` or `-1` marks a wildcard cell
+    pub fn parse_text(text_path: &str) -> Result<Self> {
+        Self::from_text(&std::fs::read_to_string(text_path)?)
+    }
+
+    /// Parse a goal written in the puzzle format, e.g. `3  1 2 3  * * *  * * *`
+    pub(crate) fn from_text(text: &str) -> Result<Self> {
+        let (size, cells) = Puzzle::parse_cells(text)?;
+        Self::from_cells(size, &cells)
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.target.size
+    }
+
+    pub fn target(&self) -> &Puzzle {
+        &self.target
+    }
+
+    /// Goal position of the given value
+    pub fn position(&self, value: usize) -> Pos {
+        self.positions[value]
+    }
+
+    /// Index of the goal position of the given value in reading order
+    pub fn index(&self, value: usize) -> usize {
+        let pos = self.positions[value];
+        pos.y * self.target.size + pos.x
+    }
+
+    /// Whether the value has to reach its goal position
+    pub fn is_fixed(&self, value: usize) -> bool {
+        self.fixed[value]
+    }
+
+    /// Whether some values are wildcards
+    pub fn is_partial(&self) -> bool {
+        self.fixed.contains(&false)
+    }
+
+    /// The same goal with the exact distances to its target
+    pub(crate) fn with_oracle(mut self, oracle: Arc<Oracle>) -> Self {
+        self.oracle = Some(oracle);
+        self
+    }
+
+    pub(crate) fn oracle(&self) -> Option<&Oracle> {
+        self.oracle.as_deref()
+    }
+
+    /// Fail for partial goals, which some searches cannot handle
+    pub fn check_complete(&self) -> Result<()> {
+        if self.is_partial() {
+            return Err(anyhow!("This needs a goal without wildcards."));
+        }
+        Ok(())
+    }
+
+    /// The same goal with two wildcard values swapped on the target.
+    /// Two wildcard tiles are preferred, since swapping them always changes the parity of the target,
+    /// whereas swapping the blank with a tile at an odd distance does not.
+    pub(super) fn swap_wildcards(&self) -> Option<Self> {
+        let mut wildcards: Vec<usize> = (1..self.fixed.len())
+            .filter(|&value| !self.fixed[value])
+            .collect();
+        if !self.fixed[0] && wildcards.len() < 2 {
+            wildcards.insert(0, 0);
+        }
+        let (first, second) = match wildcards[..] {
+            [first, second, ..] => (first, second),
+            _ => return None,
+        };
+        let mut state = self.target.state.clone();
+        let (a, b) = (self.positions[first], self.positions[second]);
+        state[a.y][a.x] = second;
+        state[b.y][b.x] = first;
+        let mut goal = Self::from_puzzle(Puzzle::new_from_state(state).ok()?);
+        goal.fixed = self.fixed.clone();
+        Some(goal)
+    }
+
+    pub fn is_reached(&self, puzzle: &Puzzle) -> bool {
+        if !self.is_partial() {
+            return puzzle.state == self.target.state;
+        }
+        (0..self.fixed.len()).all(|value| {
+            let pos = self.positions[value];
+            !self.fixed[value] || puzzle.state[pos.y][pos.x] == value
+        })
+    }
+
+    pub fn check_size(&self, puzzle: &Puzzle) -> Result<()> {
+        if puzzle.size != self.target.size {
+            return Err(anyhow!(
+                "Goal size {} does not match puzzle size {}.",
+                self.target.size,
+                puzzle.size
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snail() {
+        let goal = Goal::snail(3);
+        assert_eq!(goal.position(0), Pos::new(1, 1));
+        assert_eq!(goal.position(4), Pos::new(2, 1));
+        assert_eq!(goal.index(7), 6);
+        assert!(goal.is_reached(&Puzzle::new_answer(3)));
+    }
+
+    #[test]
+    fn test_row_major() -> Result<()> {
+        let goal = Goal::row_major(3);
+        assert_eq!(goal.position(0), Pos::new(0, 0));
+        assert_eq!(goal.position(5), Pos::new(2, 1));
+        let puzzle = Puzzle::new_from_state(vec![vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8]])?;
+        assert!(goal.is_reached(&puzzle));
+        assert!(!goal.is_reached(&Puzzle::new_answer(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_partial() -> Result<()> {
+        let goal = Goal::parse_text("../puzzles/first_row_target.txt")?;
+        assert!(goal.is_partial());
+        assert!(goal.is_fixed(2));
+        assert!(!goal.is_fixed(0));
+        assert_eq!(goal.target().get(Pos::new(0, 1))?, 0);
+        assert!(goal.is_reached(&Puzzle::new_answer(3)));
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![0, 8, 7], vec![6, 5, 4]])?;
+        assert!(goal.is_reached(&puzzle));
+        assert!(!goal.is_reached(&Goal::row_major(3).target().clone()));
+        assert!(goal.check_complete().is_err());
+        let swapped = goal.swap_wildcards().unwrap();
+        assert_eq!(swapped.target().get(Pos::new(1, 1))?, 5);
+        assert!(Goal::from_cells(2, &[Some(1), Some(1), None, None]).is_err());
+        assert!(Goal::snail(3).swap_wildcards().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_size() {
+        let goal = Goal::snail(3);
+        assert!(goal.check_size(&Puzzle::new_answer(3)).is_ok());
+        assert!(goal.check_size(&Puzzle::new_answer(4)).is_err());
+    }
+}
@@ -5,43 +5,128 @@ use anyhow::{anyhow, Result};
 use super::Puzzle;
 use std::fs;
 
+/// Separator line between puzzles in a multi-puzzle file
+const PUZZLE_SEPARATOR: &str = "---";
+
+fn remove_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(index) => &line[0..index],
+        _ => line,
+    }
+}
+
+/// Cell of a goal file that may hold any value
+fn is_wildcard(element: &str) -> bool {
+    element == "*" || element == "-1"
+}
+
+/// Elements of a text without its comments
+fn split_elements(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(remove_comment)
+        .flat_map(|line| line.split_whitespace())
+        .collect()
+}
+
+/// Size at the start of the elements of a puzzle, followed by at least one element per cell
+fn parse_size(elements: &[&str]) -> Result<usize> {
+    let size: &str = match elements.first() {
+        Some(&elem) => elem,
+        None => return Err(anyhow!("Cannot find the size.")),
+    };
+    let size: usize = size.parse()?;
+    if !(2..=MAX_PUZZLE_SIZE).contains(&size) {
+        return Err(anyhow!(
+            "Invalid puzzle size: {}. Must be between 2 and {}.",
+            size,
+            MAX_PUZZLE_SIZE
+        ));
+    }
+    if elements.len() < size * size + 1 {
+        return Err(anyhow!(
+            "Number of elements does not match puzzle size: {}.",
+            size
+        ));
+    }
+    Ok(size)
+}
+
+/// Size of the only puzzle of the elements
+fn parse_single_size(elements: &[&str]) -> Result<usize> {
+    let size = parse_size(elements)?;
+    if elements.len() != size * size + 1 {
+        return Err(anyhow!(
+            "Number of elements does not match puzzle size: {}.",
+            size
+        ));
+    }
+    Ok(size)
+}
+
 impl Puzzle {
+    /// Parse a board whose cells may be wildcards, returned as `None`
+    pub(super) fn parse_cells(text: &str) -> Result<(usize, Vec<Option<usize>>)> {
+        let elements = split_elements(text);
+        let size = parse_single_size(&elements)?;
+        let mut cells = Vec::with_capacity(size * size);
+        for element in &elements[1..] {
+            if is_wildcard(element) {
+                cells.push(None);
+            } else {
+                cells.push(Some(element.parse::<usize>()?));
+            }
+        }
+        Ok((size, cells))
+    }
+
     pub(super) fn parse_text(text_path: String) -> Result<Self> {
         let text = fs::read_to_string(text_path)?;
-        let text_without_comments: String = text
-            .lines()
-            .map(|line| match line.find('#') {
-                Some(index) => &line[0..index],
-                _ => line,
-            })
-            .collect::<Vec<&str>>()
-            .join(" ");
-        let mut elements: Vec<&str> = text_without_comments.split_whitespace().collect();
-
-        let size: &str = match elements.first() {
-            Some(&elem) => elem,
-            None => return Err(anyhow!("Cannot find the size.")),
-        };
-        elements.remove(0);
-        let size: usize = size.parse()?;
-        if !(2..=MAX_PUZZLE_SIZE).contains(&size) {
-            return Err(anyhow!(
-                "Invalid puzzle size: {}. Must be between 2 and {}.",
-                size,
-                MAX_PUZZLE_SIZE
-            ));
+        let elements = split_elements(&text);
+        parse_single_size(&elements)?;
+        Self::parse_elements(&elements)
+    }
+
+    /// Parse a file containing one or more puzzles.
+    ///
+    /// Every puzzle starts with its size, so that puzzles may simply follow each other;
+    /// a line containing only `---` may also separate them.
+    pub fn parse_texts(text_path: &str) -> Result<Vec<Self>> {
+        let text = fs::read_to_string(text_path)?;
+        let mut puzzles = Vec::new();
+        let mut elements: Vec<&str> = Vec::new();
+        for line in text.lines().map(remove_comment) {
+            if line.trim() == PUZZLE_SEPARATOR {
+                puzzles.append(&mut Self::parse_sequence(&elements)?);
+                elements.clear();
+            } else {
+                elements.extend(line.split_whitespace());
+            }
+        }
+        puzzles.append(&mut Self::parse_sequence(&elements)?);
+        if puzzles.is_empty() {
+            return Err(anyhow!("Cannot find any puzzle."));
+        }
+        Ok(puzzles)
+    }
+
+    /// Parse consecutive puzzles, each starting with its size
+    fn parse_sequence(mut elements: &[&str]) -> Result<Vec<Self>> {
+        let mut puzzles = Vec::new();
+        while !elements.is_empty() {
+            let puzzle = Self::parse_elements(elements)?;
+            elements = &elements[puzzle.size * puzzle.size + 1..];
+            puzzles.push(puzzle);
         }
+        Ok(puzzles)
+    }
 
+    /// Parse the size followed by the values of a single puzzle
+    fn parse_elements(elements: &[&str]) -> Result<Self> {
+        let size = parse_size(elements)?;
         let mut state = vec![vec![0; size]; size];
         let mut blank_pos = Pos::new(0, 0);
 
-        if elements.len() != size * size {
-            return Err(anyhow!(
-                "Number of elements does not match puzzle size: {}.",
-                size
-            ));
-        }
-        for (index, element) in elements.iter().enumerate() {
+        for (index, element) in elements[1..size * size + 1].iter().enumerate() {
             let val = element.parse::<usize>()?;
             let pos = Pos::new(index % size, index / size);
             state[pos.y][pos.x] = val;
@@ -51,14 +136,46 @@ impl Puzzle {
             }
         }
 
-        let puzzle = Self {
-            size,
-            state,
-            blank_pos,
-        };
+        let puzzle = Self::build(size, state, blank_pos);
         if !puzzle.check_state() {
             return Err(anyhow!("Invalid puzzle format."));
         }
         Ok(puzzle)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sequence() -> Result<()> {
+        let elements = vec!["2", "1", "2", "0", "3", "2", "0", "1", "2", "3"];
+        let puzzles = Puzzle::parse_sequence(&elements)?;
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[0].state, vec![vec![1, 2], vec![0, 3]]);
+        assert_eq!(puzzles[1].state, vec![vec![0, 1], vec![2, 3]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_sequence_incomplete() {
+        let elements = vec!["2", "1", "2", "0", "3", "2", "0", "1"];
+        assert!(Puzzle::parse_sequence(&elements).is_err());
+    }
+
+    #[test]
+    fn test_parse_texts_single() -> Result<()> {
+        let puzzles = Puzzle::parse_texts("../puzzles/comment_puzzle.txt")?;
+        assert_eq!(puzzles.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_texts_multiple() -> Result<()> {
+        let puzzles = Puzzle::parse_texts("../puzzles/batch_puzzles.txt")?;
+        assert_eq!(puzzles.len(), 3);
+        assert!(puzzles[1].is_final_state());
+        Ok(())
+    }
+}
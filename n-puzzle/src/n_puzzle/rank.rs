@@ -0,0 +1,237 @@
+use super::{Goal, Pos, Puzzle};
+use anyhow::{anyhow, Result};
+
+/// 20! is the largest factorial that fits in a u64
+const MAX_CELLS: usize = 20;
+
+fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
+}
+
+fn check_cells(size: usize) -> Result<usize> {
+    let cells = size * size;
+    if size < 2 || cells > MAX_CELLS {
+        return Err(anyhow!(
+            "Ranking only supports puzzles from 2x2 up to 4x4, not {}x{}.",
+            size,
+            size
+        ));
+    }
+    Ok(cells)
+}
+
+/// Lehmer code of distinct values, relative to the set of the values
+fn lehmer_rank(values: &[usize]) -> u64 {
+    let mut rank = 0;
+    for (i, value) in values.iter().enumerate() {
+        let smaller = values[i + 1..].iter().filter(|&v| v < value).count() as u64;
+        rank += smaller * factorial(values.len() - 1 - i);
+    }
+    rank
+}
+
+fn lehmer_unrank(mut available: Vec<usize>, mut rank: u64) -> Vec<usize> {
+    let mut values = Vec::with_capacity(available.len());
+    while !available.is_empty() {
+        let weight = factorial(available.len() - 1);
+        values.push(available.remove((rank / weight) as usize));
+        rank %= weight;
+    }
+    values
+}
+
+/// Rank of a board given in reading order: the blank index is the most significant digit,
+/// followed by the Lehmer code of the tiles.
+/// The ranks of the boards of a size are exactly `0..(size * size)!`.
+pub(crate) fn rank_cells(cells: &[usize]) -> u64 {
+    let blank = cells.iter().position(|&v| v == 0).unwrap_or(0);
+    let tiles: Vec<usize> = cells.iter().copied().filter(|&v| v != 0).collect();
+    blank as u64 * factorial(tiles.len()) + lehmer_rank(&tiles)
+}
+
+fn unrank_cells(cells: usize, rank: u64) -> Vec<usize> {
+    let weight = factorial(cells - 1);
+    let blank = (rank / weight) as usize;
+    let mut values = lehmer_unrank((1..cells).collect(), rank % weight);
+    values.insert(blank, 0);
+    values
+}
+
+impl Puzzle {
+    fn from_cells(size: usize, cells: &[usize]) -> Result<Self> {
+        Self::new_from_state(cells.chunks(size).map(|row| row.to_vec()).collect())
+    }
+
+    fn cells(&self) -> Vec<usize> {
+        self.state.iter().flatten().copied().collect()
+    }
+
+    /// Dense index of the board among the `(size * size)!` boards of its size.
+    /// Boards with the same blank position have consecutive ranks.
+    pub fn rank(&self) -> Result<u64> {
+        check_cells(self.size)?;
+        Ok(rank_cells(&self.cells()))
+    }
+
+    pub fn unrank(size: usize, rank: u64) -> Result<Self> {
+        let cells = check_cells(size)?;
+        if rank >= factorial(cells) {
+            return Err(anyhow!("Rank {} is out of range for size {}.", rank, size));
+        }
+        Self::from_cells(size, &unrank_cells(cells, rank))
+    }
+
+    /// Dense index of the board among the `(size * size)! / 2` boards that can reach the goal.
+    ///
+    /// Swapping the last two tiles flips the solvability and changes the Lehmer code by one,
+    /// so exactly one of the tile ranks `2k` and `2k + 1` is solvable.
+    pub fn rank_solvable(&self, goal: &Goal) -> Result<u64> {
+        if !self.is_solvable_to(goal)? {
+            return Err(anyhow!("The puzzle cannot reach the goal."));
+        }
+        let rank = self.rank()?;
+        let weight = factorial(self.size * self.size - 1);
+        Ok(rank / weight * (weight / 2) + rank % weight / 2)
+    }
+
+    pub fn unrank_solvable(size: usize, rank: u64, goal: &Goal) -> Result<Self> {
+        let cells = check_cells(size)?;
+        if rank >= factorial(cells) / 2 {
+            return Err(anyhow!("Rank {} is out of range for size {}.", rank, size));
+        }
+        let half = factorial(cells - 1) / 2;
+        let first = (rank / half) * half * 2 + rank % half * 2;
+        let puzzle = Self::unrank(size, first)?;
+        if puzzle.is_solvable_to(goal)? {
+            return Ok(puzzle);
+        }
+        Self::unrank(size, first + 1)
+    }
+
+    /// Every board of the given size, in rank order
+    pub fn all_states(size: usize) -> Result<States> {
+        let cells = check_cells(size)?;
+        Ok(States {
+            size,
+            next: 0,
+            end: factorial(cells),
+            goal: None,
+        })
+    }
+
+    /// Every board of the given size with the blank at the given position, in rank order
+    pub fn states_with_blank(size: usize, blank_pos: Pos) -> Result<States> {
+        let cells = check_cells(size)?;
+        if blank_pos.x >= size || blank_pos.y >= size {
+            return Err(anyhow!("Blank position is out of the board."));
+        }
+        let weight = factorial(cells - 1);
+        let first = (blank_pos.y * size + blank_pos.x) as u64 * weight;
+        Ok(States {
+            size,
+            next: first,
+            end: first + weight,
+            goal: None,
+        })
+    }
+
+    /// Every board of the given size that can reach the goal, in solvable rank order
+    pub fn solvable_states(goal: &Goal) -> Result<States> {
+        let cells = check_cells(goal.get_size())?;
+        Ok(States {
+            size: goal.get_size(),
+            next: 0,
+            end: factorial(cells) / 2,
+            goal: Some(goal.clone()),
+        })
+    }
+}
+
+/// Iterator over a range of ranks
+#[derive(Debug, Clone)]
+pub struct States {
+    size: usize,
+    next: u64,
+    end: u64,
+    /// Ranks are solvable ranks towards this goal when set
+    goal: Option<Goal>,
+}
+
+impl Iterator for States {
+    type Item = Puzzle;
+
+    fn next(&mut self) -> Option<Puzzle> {
+        if self.next >= self.end {
+            return None;
+        }
+        let puzzle = match &self.goal {
+            Some(goal) => Puzzle::unrank_solvable(self.size, self.next, goal),
+            None => Puzzle::unrank(self.size, self.next),
+        };
+        self.next += 1;
+        puzzle.ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![vec![0, 1], vec![2, 3]])?;
+        assert_eq!(puzzle.rank()?, 0);
+        let puzzle = Puzzle::new_from_state(vec![vec![3, 2], vec![1, 0]])?;
+        assert_eq!(puzzle.rank()?, 23);
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 0], vec![2, 3]])?;
+        assert_eq!(puzzle.rank()?, 6);
+        assert!(Puzzle::new_answer(5).rank().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unrank() -> Result<()> {
+        for rank in 0..24 {
+            assert_eq!(Puzzle::unrank(2, rank)?.rank()?, rank);
+        }
+        let puzzle = Puzzle::new_answer(4);
+        assert_eq!(Puzzle::unrank(4, puzzle.rank()?)?, puzzle);
+        assert!(Puzzle::unrank(2, 24).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rank_solvable() -> Result<()> {
+        let goal = Goal::snail(3);
+        let mut ranks: Vec<u64> = Puzzle::all_states(3)?
+            .filter(|puzzle| puzzle.is_solvable_to(&goal).unwrap())
+            .map(|puzzle| puzzle.rank_solvable(&goal).unwrap())
+            .collect();
+        ranks.sort();
+        assert_eq!(ranks, (0..181_440).collect::<Vec<u64>>());
+        let puzzle = Puzzle::unrank_solvable(3, 12345, &goal)?;
+        assert_eq!(puzzle.rank_solvable(&goal)?, 12345);
+        let unsolvable = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert!(unsolvable.rank_solvable(&goal).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_states() -> Result<()> {
+        assert_eq!(Puzzle::all_states(2)?.count(), 24);
+        let states: Vec<Puzzle> = Puzzle::states_with_blank(3, Pos::new(2, 1))?.collect();
+        assert_eq!(states.len(), 40_320);
+        assert!(states.iter().all(|p| p.get_blank_pos() == Pos::new(2, 1)));
+        let goal = Goal::row_major(2);
+        let states: Vec<Puzzle> = Puzzle::solvable_states(&goal)?.collect();
+        assert_eq!(states.len(), 12);
+        assert!(states.iter().all(|p| p.is_solvable_to(&goal).unwrap()));
+        assert!(Puzzle::states_with_blank(2, Pos::new(2, 0)).is_err());
+        Ok(())
+    }
+}
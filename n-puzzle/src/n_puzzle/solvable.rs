@@ -1,4 +1,4 @@
-use super::Puzzle;
+use super::{Goal, Puzzle, Topology};
 use anyhow::{anyhow, Result};
 
 impl Puzzle {
@@ -8,14 +8,37 @@ impl Puzzle {
     ///
     /// - https://manabitimes.jp/math/979
     /// - https://edu-gw2.math.cst.nihon-u.ac.jp/~kurino/2006/linear/permutation/permutation.pdf
-    pub(super) fn is_solvable(&self) -> Result<bool> {
+    pub fn is_solvable(&self) -> Result<bool> {
+        self.is_solvable_to(&Goal::snail(self.size))
+    }
+
+    /// Check if the given goal can be reached from the puzzle.
+    /// A partial goal is reached through either of its targets of opposite parity.
+    pub fn is_solvable_to(&self, goal: &Goal) -> Result<bool> {
+        if self.has_parity_of(goal)? {
+            return Ok(true);
+        }
+        match goal.swap_wildcards() {
+            Some(swapped) => self.has_parity_of(&swapped),
+            None => Ok(false),
+        }
+    }
+
+    /// Check if the target of the goal can be reached from the puzzle.
+    /// On a torus of odd size, the blank going round a row comes back after an odd number of swaps,
+    /// so that every board can be reached. A wrapping move on a torus of even size still moves
+    /// the blank by an odd distance, so that the parity is kept as on the plane.
+    fn has_parity_of(&self, goal: &Goal) -> Result<bool> {
+        goal.check_size(self)?;
+        if self.topology == Topology::Torus && self.size % 2 == 1 {
+            return Ok(true);
+        }
         let mut count = 0;
-        let mut flat_state: Vec<usize> = self.state.iter().flatten().copied().collect();
-        let answer_map = Puzzle::generate_arrange_order_answer_map(self.size, false);
-        flat_state = flat_state
+        let mut flat_state: Vec<usize> = self
+            .state
             .iter()
-            .map(|&x| answer_map.get(&x).unwrap())
-            .copied()
+            .flatten()
+            .map(|&x| goal.index(x) + 1)
             .collect();
         for i in 0..flat_state.len() {
             if flat_state[i] == i + 1 {
@@ -30,9 +53,9 @@ impl Puzzle {
             count += 1;
         }
         let blank_pos = self.blank_pos;
-        let answer = Puzzle::new_answer(self.size);
-        let blank_pos_diff = (blank_pos.x as isize - answer.blank_pos.x as isize).abs()
-            + (blank_pos.y as isize - answer.blank_pos.y as isize).abs();
+        let answer_blank_pos = goal.position(0);
+        let blank_pos_diff = (blank_pos.x as isize - answer_blank_pos.x as isize).abs()
+            + (blank_pos.y as isize - answer_blank_pos.y as isize).abs();
         Ok((count + blank_pos_diff) % 2 == 0)
     }
 }
@@ -40,6 +63,18 @@ impl Puzzle {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Move, Pos};
+
+    #[test]
+    fn test_is_solvable_to_partial() -> Result<()> {
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        let goal = Goal::from_text("3  * * 3  8 0 4  7 6 5")?;
+        assert!(puzzle.is_solvable_to(&goal)?);
+        // The blank and the 4 can only trade places by moving, which keeps the parity
+        let goal = Goal::from_text("3  1 2 3  8 * *  7 6 5")?;
+        assert!(!puzzle.is_solvable_to(&goal)?);
+        Ok(())
+    }
 
     #[test]
     fn test_is_trivial_solvable() -> Result<()> {
@@ -85,4 +120,46 @@ mod tests {
         assert!(!puzzle.is_solvable()?);
         Ok(())
     }
+
+    #[test]
+    fn test_is_solvable_torus() -> Result<()> {
+        let mut puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 0, 7], vec![4, 6, 5]])?;
+        puzzle.set_topology(Topology::Torus);
+        assert!(puzzle.is_solvable()?);
+        let mut puzzle = Puzzle::new_from_state(vec![
+            vec![8, 1, 7, 3],
+            vec![5, 2, 6, 12],
+            vec![11, 0, 4, 14],
+            vec![10, 13, 9, 15],
+        ])?;
+        puzzle.set_topology(Topology::Torus);
+        assert!(!puzzle.is_solvable()?);
+        // Wrapping to the other edge moves the blank by an odd distance
+        puzzle.move_blank(Move::Down)?;
+        puzzle.move_blank(Move::Down)?;
+        assert_eq!(puzzle.get_blank_pos(), Pos::new(1, 0));
+        assert!(!puzzle.is_solvable()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_solvable_to_row_major() -> Result<()> {
+        let goal = Goal::row_major(4);
+        let puzzle = Puzzle::new_from_state(vec![
+            vec![14, 13, 15, 7],
+            vec![11, 12, 9, 5],
+            vec![6, 0, 2, 1],
+            vec![4, 8, 10, 3],
+        ])?;
+        assert!(puzzle.is_solvable_to(&goal)?);
+        let puzzle = Puzzle::new_from_state(vec![
+            vec![1, 0, 2, 3],
+            vec![4, 5, 6, 7],
+            vec![8, 9, 10, 11],
+            vec![12, 13, 15, 14],
+        ])?;
+        assert!(!puzzle.is_solvable_to(&goal)?);
+        assert!(puzzle.is_solvable_to(&Goal::snail(3)).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,67 @@
+use super::{Pos, Puzzle};
+
+const CELL_SIZE: usize = 48;
+const CELL_MARGIN: usize = 2;
+
+impl Puzzle {
+    /// Render the puzzle as a standalone SVG image
+    pub fn to_svg(&self) -> String {
+        let width = self.size * CELL_SIZE;
+        let digits = (self.size * self.size - 1).to_string().len();
+        let font_size = (CELL_SIZE * 2 / 5).min(CELL_SIZE * 4 / 5 / digits.max(1));
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+            width
+        );
+        svg += format!(
+            "<rect width=\"{0}\" height=\"{0}\" fill=\"#3c3c3c\"/>",
+            width
+        )
+        .as_str();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let val = self.get(Pos::new(x, y)).unwrap();
+                if val == 0 {
+                    continue;
+                }
+                let left = x * CELL_SIZE + CELL_MARGIN;
+                let top = y * CELL_SIZE + CELL_MARGIN;
+                svg += format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" rx=\"4\" fill=\"#f0d9b5\"/>",
+                    left,
+                    top,
+                    CELL_SIZE - CELL_MARGIN * 2
+                )
+                .as_str();
+                svg += format!(
+                    "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"#222\">{}</text>",
+                    x * CELL_SIZE + CELL_SIZE / 2,
+                    y * CELL_SIZE + CELL_SIZE / 2,
+                    font_size,
+                    val
+                )
+                .as_str();
+            }
+        }
+        svg += "</svg>";
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let puzzle = Puzzle::new_answer(3);
+        let svg = puzzle.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"144\""));
+        assert!(svg.ends_with("</svg>"));
+        // One background and eight tiles
+        assert_eq!(svg.matches("<rect").count(), 9);
+        assert_eq!(svg.matches("<text").count(), 8);
+        assert!(svg.contains(">8</text>"));
+        assert!(!svg.contains(">0</text>"));
+    }
+}
@@ -0,0 +1,103 @@
+use super::Puzzle;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+/// Zobrist key of a tile on a cell, 0 for the blank.
+///
+/// The keys are derived from the cell index and the value by a mixing function instead of
+/// a random table, so that they are the same in every run and for boards of every size.
+pub(super) fn tile_key(index: usize, value: usize) -> u64 {
+    if value == 0 {
+        return 0;
+    }
+    // splitmix64 finalizer
+    let mut key = ((index as u64) << 32 | value as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    key ^ (key >> 31)
+}
+
+/// Zobrist key of a whole board: the exclusive or of the keys of its tiles
+pub(super) fn state_key(state: &[Vec<usize>]) -> u64 {
+    let size = state.len();
+    let mut key = 0;
+    for (y, row) in state.iter().enumerate() {
+        for (x, &value) in row.iter().enumerate() {
+            key ^= tile_key(y * size + x, value);
+        }
+    }
+    key
+}
+
+impl Puzzle {
+    /// Zobrist key of the board, updated in constant time by every move.
+    /// Equal boards have equal keys, and distinct boards almost never share one.
+    pub fn zobrist_key(&self) -> u64 {
+        self.zobrist
+    }
+}
+
+impl Hash for Puzzle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist);
+    }
+}
+
+/// Hasher that uses the Zobrist key of a board as is, for maps keyed by boards
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ZobristHasher(u64);
+
+impl Hasher for ZobristHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // FNV-1a, for keys other than a single board
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, key: u64) {
+        self.0 ^= key;
+    }
+}
+
+pub type ZobristBuildHasher = BuildHasherDefault<ZobristHasher>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Move, Pos};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_zobrist_incremental() -> anyhow::Result<()> {
+        let mut puzzle = Puzzle::new_answer(4);
+        for m in [Move::Left, Move::Up, Move::Right, Move::Right, Move::Down] {
+            puzzle.move_blank(m)?;
+            assert_eq!(puzzle.zobrist_key(), state_key(&puzzle.state));
+        }
+        puzzle.swap(Pos::new(0, 0), Pos::new(3, 3))?;
+        assert_eq!(puzzle.zobrist_key(), state_key(&puzzle.state));
+        puzzle.unchecked_set(Pos::new(1, 0), 0)?;
+        assert_eq!(puzzle.zobrist_key(), state_key(&puzzle.state));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zobrist_distinct() -> anyhow::Result<()> {
+        let keys: HashSet<u64> = Puzzle::all_states(3)?
+            .map(|puzzle| puzzle.zobrist_key())
+            .collect();
+        assert_eq!(keys.len(), 362880);
+        let back = {
+            let mut puzzle = Puzzle::new_answer(3);
+            puzzle.move_blank(Move::Up)?;
+            puzzle.move_blank(Move::Down)?;
+            puzzle
+        };
+        assert_eq!(back.zobrist_key(), Puzzle::new_answer(3).zobrist_key());
+        Ok(())
+    }
+}
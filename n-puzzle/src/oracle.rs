@@ -0,0 +1,258 @@
+use crate::n_puzzle::rank_cells;
+use crate::{Goal, Move, Pos, Puzzle};
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+/// Only the 8-puzzle state space is small enough to be kept in memory
+const SIZE: usize = 3;
+const CELLS: usize = SIZE * SIZE;
+/// 9! permutations, half of them solvable
+const STATES: usize = 362_880;
+/// Distance of the states that cannot reach the goal
+const UNREACHABLE: u8 = u8::MAX;
+const FILE_HEADER: &[u8] = b"n-puzzle oracle 2\n";
+
+/// Oracles already built, shared by every search
+static ORACLES: Mutex<Vec<Arc<Oracle>>> = Mutex::new(Vec::new());
+
+/// Optimal distance and optimal first moves of a 3x3 board
+#[derive(Debug, Clone, PartialEq)]
+pub struct OracleAnswer {
+    pub distance: usize,
+    pub moves: Vec<Move>,
+}
+
+/// Exact distance to the goal of every 3x3 board
+pub struct Oracle {
+    goal: [usize; CELLS],
+    distances: Vec<u8>,
+}
+
+impl std::fmt::Debug for Oracle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Oracle")
+            .field("goal", &self.goal)
+            .finish_non_exhaustive()
+    }
+}
+
+fn rank(cells: &[usize; CELLS]) -> usize {
+    rank_cells(cells) as usize
+}
+
+fn to_cells(puzzle: &Puzzle) -> Result<[usize; CELLS]> {
+    if puzzle.get_size() != SIZE {
+        return Err(anyhow!(
+            "The oracle only knows 3x3 puzzles, not {}x{}.",
+            puzzle.get_size(),
+            puzzle.get_size()
+        ));
+    }
+    let mut cells = [0; CELLS];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        *cell = puzzle.get(Pos::new(i % SIZE, i / SIZE))?;
+    }
+    Ok(cells)
+}
+
+/// Board after moving the blank, if the move stays on the board
+fn move_cells(cells: &[usize; CELLS], m: Move) -> Option<[usize; CELLS]> {
+    let blank = cells.iter().position(|&v| v == 0)?;
+    let (x, y) = (blank % SIZE, blank / SIZE);
+    let target = match m {
+        Move::Up if y > 0 => blank - SIZE,
+        Move::Down if y < SIZE - 1 => blank + SIZE,
+        Move::Left if x > 0 => blank - 1,
+        Move::Right if x < SIZE - 1 => blank + 1,
+        _ => return None,
+    };
+    let mut next = *cells;
+    next.swap(blank, target);
+    Some(next)
+}
+
+impl Oracle {
+    /// Breadth-first search over the whole state space from the goal
+    pub fn build(goal: &Goal) -> Result<Self> {
+        goal.check_complete()?;
+        let goal = to_cells(goal.target())?;
+        let mut distances = vec![UNREACHABLE; STATES];
+        let mut queue = VecDeque::new();
+        distances[rank(&goal)] = 0;
+        queue.push_back(goal);
+        while let Some(cells) = queue.pop_front() {
+            let distance = distances[rank(&cells)];
+            for m in Move::list() {
+                if let Some(next) = move_cells(&cells, m) {
+                    let next_rank = rank(&next);
+                    if distances[next_rank] == UNREACHABLE {
+                        distances[next_rank] = distance + 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        Ok(Self { goal, distances })
+    }
+
+    /// Oracle of the given goal, built once and shared afterwards
+    pub fn get(goal: &Goal) -> Result<Arc<Self>> {
+        goal.check_complete()?;
+        let cells = to_cells(goal.target())?;
+        let mut oracles = ORACLES
+            .lock()
+            .map_err(|_| anyhow!("Oracle cache poisoned"))?;
+        if let Some(oracle) = oracles.iter().find(|oracle| oracle.goal == cells) {
+            return Ok(Arc::clone(oracle));
+        }
+        let oracle = Arc::new(Self::build(goal)?);
+        oracles.push(Arc::clone(&oracle));
+        Ok(oracle)
+    }
+
+    /// Oracle of the given goal read from `path`, or built and written to `path`
+    /// when the file does not exist yet
+    pub fn get_cached(goal: &Goal, path: &str) -> Result<Arc<Self>> {
+        goal.check_complete()?;
+        let oracle = match fs::metadata(path) {
+            Ok(_) => Self::load(path)?,
+            Err(_) => {
+                let oracle = Self::get(goal)?;
+                oracle.save(path)?;
+                return Ok(oracle);
+            }
+        };
+        if oracle.goal != to_cells(goal.target())? {
+            return Err(anyhow!(
+                "The oracle in {} was built for another goal.",
+                path
+            ));
+        }
+        let oracle = Arc::new(oracle);
+        let mut oracles = ORACLES
+            .lock()
+            .map_err(|_| anyhow!("Oracle cache poisoned"))?;
+        oracles.retain(|cached| cached.goal != oracle.goal);
+        oracles.push(Arc::clone(&oracle));
+        Ok(oracle)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut bytes = FILE_HEADER.to_vec();
+        bytes.extend(self.goal.iter().map(|&v| v as u8));
+        bytes.extend_from_slice(&self.distances);
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = || anyhow!("Not a valid oracle file: {}", path);
+        let body = bytes.strip_prefix(FILE_HEADER).ok_or_else(invalid)?;
+        if body.len() != CELLS + STATES {
+            return Err(invalid());
+        }
+        let mut goal = [0; CELLS];
+        for (cell, &value) in goal.iter_mut().zip(&body[..CELLS]) {
+            *cell = value as usize;
+        }
+        let mut sorted = goal;
+        sorted.sort();
+        if sorted.iter().enumerate().any(|(i, &v)| v != i) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            goal,
+            distances: body[CELLS..].to_vec(),
+        })
+    }
+
+    /// Optimal number of moves to the goal, `None` if the goal cannot be reached
+    pub fn distance(&self, puzzle: &Puzzle) -> Result<Option<usize>> {
+        let distance = self.distances[rank(&to_cells(puzzle)?)];
+        if distance == UNREACHABLE {
+            return Ok(None);
+        }
+        Ok(Some(distance as usize))
+    }
+
+    /// Optimal distance and every first move that starts an optimal solution
+    pub fn query(&self, puzzle: &Puzzle) -> Result<OracleAnswer> {
+        let cells = to_cells(puzzle)?;
+        let distance = self.distances[rank(&cells)];
+        if distance == UNREACHABLE {
+            return Err(anyhow!("This puzzle is unsolvable."));
+        }
+        let moves = Move::list()
+            .into_iter()
+            .filter(|m| match move_cells(&cells, *m) {
+                Some(next) => distance > 0 && self.distances[rank(&next)] == distance - 1,
+                None => false,
+            })
+            .collect();
+        Ok(OracleAnswer {
+            distance: distance as usize,
+            moves,
+        })
+    }
+}
+
+/// Exact distance used as a perfect heuristic, from the oracle attached by `Heuristic::prepare_goal`.
+/// Unsolvable boards get a distance larger than any solvable one,
+/// and nothing is known without an oracle.
+pub(crate) fn exact(puzzle: &Puzzle, goal: &Goal) -> usize {
+    match goal.oracle().map(|oracle| oracle.distance(puzzle)) {
+        Some(Ok(Some(distance))) => distance,
+        Some(_) => UNREACHABLE as usize,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(rank(&[0, 1, 2, 3, 4, 5, 6, 7, 8]), 0);
+        assert_eq!(rank(&[8, 7, 6, 5, 4, 3, 2, 1, 0]), STATES - 1);
+        assert_eq!(rank(&[0, 1, 2, 3, 4, 5, 6, 8, 7]), 1);
+        assert_eq!(rank(&[1, 0, 2, 3, 4, 5, 6, 7, 8]), STATES / CELLS);
+    }
+
+    #[test]
+    fn test_oracle() -> Result<()> {
+        let oracle = Oracle::get(&Goal::snail(3))?;
+        let reachable = oracle.distances.iter().filter(|&&d| d != UNREACHABLE);
+        assert_eq!(reachable.count(), STATES / 2);
+        assert_eq!(oracle.distance(&Puzzle::new_answer(3))?, Some(0));
+
+        let puzzle = Puzzle::new_from_state(vec![vec![1, 2, 3], vec![8, 6, 4], vec![7, 0, 5]])?;
+        let answer = oracle.query(&puzzle)?;
+        assert_eq!(answer.distance, 1);
+        assert_eq!(answer.moves, vec![Move::Up]);
+
+        let puzzle = Puzzle::new_from_state(vec![vec![2, 1, 3], vec![8, 0, 4], vec![7, 6, 5]])?;
+        assert_eq!(oracle.distance(&puzzle)?, None);
+        assert!(oracle.query(&puzzle).is_err());
+        assert!(oracle.distance(&Puzzle::new_answer(4)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let path = std::env::temp_dir().join("n_puzzle_test_oracle.bin");
+        let path = path.to_string_lossy().to_string();
+        let oracle = Oracle::get(&Goal::snail(3))?;
+        oracle.save(&path)?;
+        let loaded = Oracle::load(&path)?;
+        assert_eq!(loaded.goal, oracle.goal);
+        assert_eq!(loaded.distances, oracle.distances);
+        fs::write(&path, b"not an oracle")?;
+        assert!(Oracle::load(&path).is_err());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
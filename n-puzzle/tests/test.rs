@@ -72,3 +72,512 @@ fn test_100_puzzle() -> Result<()> {
     n_puzzle::run(args)?;
     Ok(())
 }
+
+#[test]
+fn test_export_html() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let svg_path = dir.join("n_puzzle_test_export.svg");
+    let html_path = dir.join("n_puzzle_test_export.html");
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/one_move_three.txt".into(),
+        "--svg".into(),
+        svg_path.to_string_lossy().into(),
+        "--html".into(),
+        html_path.to_string_lossy().into(),
+    ];
+
+    n_puzzle::run(args)?.unwrap();
+    let svg = std::fs::read_to_string(&svg_path)?;
+    let html = std::fs::read_to_string(&html_path)?;
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(html.matches("<figure class=\"frame\"").count(), 2);
+    std::fs::remove_file(svg_path)?;
+    std::fs::remove_file(html_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_batch() -> Result<()> {
+    let args: Vec<String> = [
+        "n-puzzle",
+        "batch",
+        "-j",
+        "2",
+        "../puzzles/batch_puzzles.txt",
+        "../puzzles/one_move_three.txt",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let summary = n_puzzle::run_batch(args)?.unwrap();
+    assert_eq!(summary.results.len(), 4);
+    assert_eq!(summary.solved(), 3);
+    assert_eq!(summary.unsolvable(), 1);
+    assert_eq!(summary.results[3].name, "../puzzles/one_move_three.txt");
+    assert_eq!(summary.median_moves(), Some(1.0));
+    Ok(())
+}
+
+#[test]
+fn test_bench() -> Result<()> {
+    let csv_path = std::env::temp_dir().join("n_puzzle_test_bench.csv");
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "bench".into(),
+        "3".into(),
+        "-n".into(),
+        "2".into(),
+        "--seed".into(),
+        "1".into(),
+        "-a".into(),
+        "astar,uniform_cost".into(),
+        "-h".into(),
+        "manhattan,linear_conflict".into(),
+        "../puzzles/one_move_three.txt".into(),
+        "--csv".into(),
+        csv_path.to_string_lossy().into(),
+    ];
+
+    let report = n_puzzle::run_bench(args)?.unwrap();
+    // 3 puzzles x (2 astar heuristics + uniform_cost)
+    assert_eq!(report.records.len(), 9);
+    let csv = std::fs::read_to_string(&csv_path)?;
+    assert_eq!(csv.lines().count(), 10);
+    assert!(csv.contains("../puzzles/one_move_three.txt,astar,manhattan,solved,1,"));
+    assert_eq!(report.to_table().lines().count(), 4);
+    std::fs::remove_file(csv_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_goal_row_major() -> Result<()> {
+    let puzzle =
+        n_puzzle::Puzzle::new_from_state(vec![vec![1, 0, 2], vec![3, 4, 5], vec![6, 7, 8]])?;
+    let mut solver = n_puzzle::Solver::new(
+        n_puzzle::Algorithm::AStar,
+        n_puzzle::Heuristic::Manhattan,
+        puzzle,
+        None,
+        false,
+    );
+    solver.set_goal(n_puzzle::Goal::row_major(3));
+    let output = solver.solve()?;
+    assert_eq!(output.path, vec![n_puzzle::Move::Left]);
+    Ok(())
+}
+
+#[test]
+fn test_checkpoint_resume() -> Result<()> {
+    let checkpoint = std::env::temp_dir().join("n_puzzle_test_checkpoint_cli.bin");
+    let checkpoint = checkpoint.to_string_lossy().to_string();
+    let puzzle = n_puzzle::Puzzle::new(n_puzzle::PuzzleSettings::TextPath(
+        "../puzzles/difficult_3_puzzle.txt".into(),
+    ))?;
+    let mut solver = n_puzzle::Solver::new(
+        n_puzzle::Algorithm::IdaStar,
+        n_puzzle::Heuristic::LinearConflict,
+        puzzle.clone(),
+        None,
+        false,
+    );
+    // A checkpoint at every expansion, the last one just before the goal
+    solver.set_checkpoint(&checkpoint, 0);
+    let output = solver.solve()?;
+
+    let args: Vec<String> = vec!["n-puzzle".into(), "--resume".into(), checkpoint.clone()];
+    let (solver, resumed) = n_puzzle::run(args)?.unwrap();
+    assert_eq!(solver.start_state(), &puzzle);
+    assert_eq!(resumed.path, output.path);
+    assert!(resumed.stats.nodes_expanded >= output.stats.nodes_expanded);
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "--resume".into(),
+        checkpoint.clone(),
+        "-a".into(),
+        "astar".into(),
+    ];
+    assert!(n_puzzle::run(args).is_err());
+    std::fs::remove_file(&checkpoint)?;
+    Ok(())
+}
+
+#[test]
+fn test_korf() -> Result<()> {
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "korf".into(),
+        "--instances".into(),
+        "79".into(),
+    ];
+
+    let summary = n_puzzle::run_korf(args.clone())?.unwrap();
+    assert_eq!(summary.results.len(), 1);
+    assert_eq!(summary.optimal(), 1);
+    assert_eq!(summary.mismatches(), 0);
+    assert_eq!(
+        n_puzzle::run_bench(args).unwrap_err().to_string(),
+        "run_bench runs the bench command. Use run_korf for the korf command."
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check() -> Result<()> {
+    let args: Vec<String> = vec!["n-puzzle".into(), "check".into(), "2".into()];
+    let report = n_puzzle::run_check(args)?.unwrap();
+    assert!(report.exhaustive);
+    assert_eq!(report.depth, None);
+    assert_eq!(report.checks.len(), 4);
+    assert!(report.checks.iter().all(|check| check.checked == 12));
+    // Every heuristic admissible on a plane passes, the ones counting the blank are reported
+    for check in &report.checks {
+        assert_eq!(
+            check.is_valid(),
+            check.heuristic.is_admissible(n_puzzle::Topology::Plane),
+            "{}",
+            check.heuristic
+        );
+    }
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "check".into(),
+        "3".into(),
+        "-h".into(),
+        "inversion_distance,linear_conflict".into(),
+        "--depth".into(),
+        "16".into(),
+        "-n".into(),
+        "500".into(),
+        "--seed".into(),
+        "1".into(),
+    ];
+    let report = n_puzzle::run_check(args)?.unwrap();
+    assert!(!report.exhaustive);
+    assert_eq!(report.depth, Some(16));
+    assert_eq!(report.checks[0].checked, 500);
+    assert!(report.checks[0].is_valid());
+    Ok(())
+}
+
+#[test]
+fn test_oracle() -> Result<()> {
+    let cache_path = std::env::temp_dir().join("n_puzzle_test_oracle_cli.bin");
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "oracle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "--cache".into(),
+        cache_path.to_string_lossy().into(),
+    ];
+    let answer = n_puzzle::run_oracle(args.clone())?.unwrap();
+    assert!(cache_path.exists());
+    assert_eq!(n_puzzle::run_oracle(args)?.unwrap(), answer);
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "-h".into(),
+        "exact".into(),
+    ];
+    let (_, output) = n_puzzle::run(args)?.unwrap();
+    assert_eq!(output.path.len(), answer.distance);
+    assert!(answer.moves.contains(&output.path[0]));
+    std::fs::remove_file(cache_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_rank_states() -> Result<()> {
+    let goal = n_puzzle::Goal::row_major(2);
+    for (rank, puzzle) in n_puzzle::Puzzle::solvable_states(&goal)?.enumerate() {
+        assert_eq!(puzzle.rank_solvable(&goal)?, rank as u64);
+        let rank = puzzle.rank()?;
+        assert_eq!(n_puzzle::Puzzle::unrank(2, rank)?, puzzle);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_hint() -> Result<()> {
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "hint".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+    ];
+    let hint = n_puzzle::run_hint(args)?.unwrap();
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "oracle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+    ];
+    let answer = n_puzzle::run_oracle(args)?.unwrap();
+    assert_eq!(hint.distance, answer.distance);
+    assert_eq!(hint.moves, answer.moves);
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "hint".into(),
+        "../puzzles/easy_4_puzzle.txt".into(),
+    ];
+    let hint = n_puzzle::run_hint(args)?.unwrap();
+    assert!(!hint.cached);
+    assert!(hint.optimal);
+    assert_eq!(hint.distance, 6);
+    assert_eq!(hint.moves, vec![n_puzzle::Move::Up]);
+    Ok(())
+}
+
+#[test]
+fn test_explore() -> Result<()> {
+    let dir = std::env::temp_dir().join("n_puzzle_test_explore_cli");
+    let _ = std::fs::remove_dir_all(&dir);
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "explore".into(),
+        "2x3".into(),
+        "--dir".into(),
+        dir.to_string_lossy().into(),
+        "--depth".into(),
+        "10".into(),
+    ];
+    let report = n_puzzle::run_explore(args.clone())?.unwrap();
+    assert_eq!(report.counts.len(), 11);
+    assert!(!report.complete);
+    let report = n_puzzle::run_explore(args[..5].to_vec())?.unwrap();
+    assert_eq!(report.resumed_depth, Some(10));
+    assert_eq!(report.states(), 360);
+    assert!(report.complete);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_optimal_solutions() -> Result<()> {
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "--solutions".into(),
+        "5".into(),
+    ];
+    let (_, output) = n_puzzle::run(args)?.unwrap();
+    let solutions = output.solutions.unwrap();
+    assert_eq!(solutions.length, output.path.len());
+    assert!(solutions.count >= 1);
+    assert_eq!(solutions.paths.len() as u64, solutions.count.min(5));
+    assert!(solutions
+        .paths
+        .iter()
+        .all(|path| path.len() == solutions.length));
+
+    // The default heuristic is not admissible, the count falls back to an admissible one
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "--count-solutions".into(),
+    ];
+    let (_, output) = n_puzzle::run(args)?.unwrap();
+    let solutions = output.solutions.unwrap();
+    assert_eq!((solutions.length, solutions.count), (24, 9));
+    Ok(())
+}
+
+#[test]
+fn test_target() -> Result<()> {
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/one_move_three.txt".into(),
+        "--target".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+    ];
+    let (solver, output) = n_puzzle::run(args)?.unwrap();
+    let target = n_puzzle::Puzzle::new(n_puzzle::PuzzleSettings::TextPath(
+        "../puzzles/difficult_3_puzzle.txt".into(),
+    ))?;
+    let mut state = solver.start_state().clone();
+    for m in &output.path {
+        state.move_blank(*m)?;
+    }
+    assert_eq!(state, target);
+    let oracle = n_puzzle::Oracle::get(&n_puzzle::Goal::from_puzzle(target))?;
+    assert_eq!(
+        Some(output.path.len()),
+        oracle.distance(solver.start_state())?
+    );
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/one_move_three.txt".into(),
+        "--target".into(),
+        "../puzzles/easy_4_puzzle.txt".into(),
+    ];
+    assert!(n_puzzle::run(args).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_partial_target() -> Result<()> {
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "--target".into(),
+        "../puzzles/first_row_target.txt".into(),
+        "--count-solutions".into(),
+    ];
+    let (solver, output) = n_puzzle::run(args)?.unwrap();
+    let goal = n_puzzle::Goal::parse_text("../puzzles/first_row_target.txt")?;
+    let mut state = solver.start_state().clone();
+    for m in &output.path {
+        state.move_blank(*m)?;
+    }
+    assert!(goal.is_reached(&state));
+    assert!(!state.is_final_state());
+    assert_eq!(output.solutions.unwrap().length, output.path.len());
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "--target".into(),
+        "../puzzles/first_row_target.txt".into(),
+        "-h".into(),
+        "exact".into(),
+    ];
+    assert!(n_puzzle::run(args).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_optimize_path() -> Result<()> {
+    use n_puzzle::{Move, Puzzle};
+    // A long walk away from the goal, replayed backwards as a solution
+    let goal = Puzzle::new_answer(3);
+    let mut start = goal.clone();
+    let mut walk = Vec::new();
+    for i in 0..200 {
+        let m = Move::list()[(i * 7 + i / 3) % 4];
+        if start.move_blank(m).is_ok() {
+            walk.push(m);
+        }
+    }
+    let path: Vec<Move> = walk.iter().rev().map(|m| m.opposite()).collect();
+    let optimization = n_puzzle::optimize_path(&start, &path, 12, None)?;
+    let mut state = start.clone();
+    for m in &optimization.path {
+        state.move_blank(*m)?;
+    }
+    assert!(state.is_final_state());
+    assert_eq!(optimization.saved(), path.len() - optimization.path.len());
+    let oracle = n_puzzle::Oracle::get(&n_puzzle::Goal::snail(3))?;
+    assert!(optimization.path.len() >= oracle.distance(&start)?.unwrap());
+    assert!(optimization.path.len() < path.len());
+
+    let args: Vec<String> = vec![
+        "n-puzzle".into(),
+        "../puzzles/difficult_3_puzzle.txt".into(),
+        "-a".into(),
+        "greedy".into(),
+        "-h".into(),
+        "exact".into(),
+        "--optimize".into(),
+    ];
+    let (_, output) = n_puzzle::run(args)?.unwrap();
+    assert_eq!(output.optimization.unwrap().saved(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_constructive() -> Result<()> {
+    let args: Vec<String> = ["n-puzzle", "40", "-a", "constructive"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let (solver, output) = n_puzzle::run(args)?.unwrap();
+    let mut state = solver.start_state().clone();
+    for m in &output.path {
+        state.move_blank(*m)?;
+    }
+    assert!(state.is_final_state());
+
+    let args: Vec<String> = ["n-puzzle", "4", "-a", "constructive", "-h", "manhattan"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert!(n_puzzle::run(args).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_hierarchical() -> Result<()> {
+    let args: Vec<String> = [
+        "n-puzzle",
+        "12",
+        "-a",
+        "hierarchical",
+        "-h",
+        "linear_conflict",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    let (solver, output) = n_puzzle::run(args)?.unwrap();
+    let mut state = solver.start_state().clone();
+    for m in &output.path {
+        state.move_blank(*m)?;
+    }
+    assert!(state.is_final_state());
+    let phases: Vec<&str> = output.phases.iter().map(|phase| phase.name).collect();
+    assert_eq!(phases, vec!["outer rings", "core"]);
+    assert_eq!(
+        output.phases.iter().map(|phase| phase.moves).sum::<usize>(),
+        output.path.len()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_torus() -> Result<()> {
+    // The puzzle has no solution on the plane, but every board can be reached on a 3x3 torus
+    let mut lengths = Vec::new();
+    for algorithm in ["astar", "ida_star", "rbfs"] {
+        let args: Vec<String> = [
+            "n-puzzle",
+            "../puzzles/unsolvable_puzzle.txt",
+            "-a",
+            algorithm,
+            "--topology",
+            "torus",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let (solver, output) = n_puzzle::run(args)?.unwrap();
+        assert_eq!(
+            solver.start_state().get_topology(),
+            n_puzzle::Topology::Torus
+        );
+        let mut state = solver.start_state().clone();
+        for m in &output.path {
+            state.move_blank(*m)?;
+        }
+        assert!(state.is_final_state());
+        lengths.push(output.path.len());
+    }
+    assert_eq!(lengths, vec![13, 13, 13]);
+
+    let args: Vec<String> = [
+        "n-puzzle",
+        "../puzzles/unsolvable_puzzle.txt",
+        "-h",
+        "linear_conflict",
+        "--topology",
+        "torus",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    assert!(n_puzzle::run(args).is_err());
+    Ok(())
+}